@@ -0,0 +1,127 @@
+use crate::{
+    interval::{self, Interval},
+    ray::Ray,
+    vec3::Point3,
+};
+
+#[derive(Clone, Copy)]
+pub struct Aabb {
+    pub x: Interval,
+    pub y: Interval,
+    pub z: Interval,
+}
+
+impl Aabb {
+    pub fn new(x: Interval, y: Interval, z: Interval) -> Self {
+        Aabb { x, y, z }
+    }
+
+    // Treat the two points as extrema for the bounding box
+    pub fn from_points(a: Point3, b: Point3) -> Self {
+        let interval = |a: f64, b: f64| {
+            if a <= b {
+                Interval::new(a, b)
+            } else {
+                Interval::new(b, a)
+            }
+        };
+        Aabb {
+            x: interval(a.x(), b.x()),
+            y: interval(a.y(), b.y()),
+            z: interval(a.z(), b.z()),
+        }
+    }
+
+    pub fn enclose(box0: &Aabb, box1: &Aabb) -> Self {
+        Aabb {
+            x: Interval::enclose(&box0.x, &box1.x),
+            y: Interval::enclose(&box0.y, &box1.y),
+            z: Interval::enclose(&box0.z, &box1.z),
+        }
+    }
+
+    // Give flat boxes (e.g. axis-aligned triangles) a little thickness so the
+    // slab test does not miss them
+    pub fn pad_to_minimums(&self) -> Self {
+        let delta = 0.0001;
+        let pad = |i: &Interval| {
+            if i.size() < delta {
+                i.expand(delta)
+            } else {
+                *i
+            }
+        };
+        Aabb {
+            x: pad(&self.x),
+            y: pad(&self.y),
+            z: pad(&self.z),
+        }
+    }
+
+    pub fn axis_interval(&self, n: usize) -> &Interval {
+        match n {
+            1 => &self.y,
+            2 => &self.z,
+            _ => &self.x,
+        }
+    }
+
+    // Index of the axis with the largest extent
+    pub fn longest_axis(&self) -> usize {
+        if self.x.size() > self.y.size() {
+            if self.x.size() > self.z.size() {
+                0
+            } else {
+                2
+            }
+        } else if self.y.size() > self.z.size() {
+            1
+        } else {
+            2
+        }
+    }
+
+    // Slab method: narrow the ray interval against each axis in turn
+    pub fn hit(&self, r: &Ray, ray_t: &Interval) -> bool {
+        let origin = r.origin();
+        let dir = r.dir();
+        let mut t = *ray_t;
+
+        for axis in 0..3 {
+            let ax = self.axis_interval(axis);
+            let adinv = 1.0 / dir[axis];
+
+            let t0 = (ax.min - origin[axis]) * adinv;
+            let t1 = (ax.max - origin[axis]) * adinv;
+
+            let (t0, t1) = if t0 < t1 { (t0, t1) } else { (t1, t0) };
+            if t0 > t.min {
+                t.min = t0;
+            }
+            if t1 < t.max {
+                t.max = t1;
+            }
+            if t.max <= t.min {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl Default for Aabb {
+    fn default() -> Self {
+        EMPTY
+    }
+}
+
+pub const EMPTY: Aabb = Aabb {
+    x: interval::EMPTY,
+    y: interval::EMPTY,
+    z: interval::EMPTY,
+};
+pub const UNIVERSE: Aabb = Aabb {
+    x: interval::UNIVERSE,
+    y: interval::UNIVERSE,
+    z: interval::UNIVERSE,
+};
@@ -0,0 +1,137 @@
+use crate::{color::Color, image::Image, utils::PI, vec3::Vec3};
+
+// Equirectangular (latitude-longitude) radiance map around the scene. The
+// image center looks down -z, its top row is straight up.
+#[derive(Clone)]
+pub struct EnvironmentMap {
+    image: Image,
+    // Turn about the y axis in degrees
+    rotation: f64,
+    intensity: f64,
+}
+
+impl EnvironmentMap {
+    pub fn new(image: Image, rotation: f64, intensity: f64) -> Self {
+        EnvironmentMap {
+            image,
+            rotation,
+            intensity,
+        }
+    }
+
+    pub fn lookup(&self, dir: &Vec3) -> Color {
+        let d = Vec3::unit_vector(dir);
+        let phi = f64::atan2(d.x(), -d.z()) - self.rotation.to_radians();
+        let u = (0.5 + phi / (2.0 * PI)).rem_euclid(1.0);
+        let v = f64::acos(d.y().clamp(-1.0, 1.0)) / PI;
+        self.intensity * self.sample(u, v)
+    }
+
+    // Bilinear filtering, wrapping around horizontally
+    fn sample(&self, u: f64, v: f64) -> Color {
+        let (w, h) = (self.image.width(), self.image.height());
+        if w == 0 || h == 0 {
+            return Color::default();
+        }
+        let x = u * w as f64 - 0.5;
+        let y = (v * h as f64 - 0.5).clamp(0.0, (h - 1) as f64);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let x0 = (x0 as i64).rem_euclid(w as i64) as usize;
+        let x1 = (x0 + 1) % w;
+        let y0 = y0 as usize;
+        let y1 = usize::min(y0 + 1, h - 1);
+
+        let top = (1.0 - fx) * self.image.get(x0, y0) + fx * self.image.get(x1, y0);
+        let bottom = (1.0 - fx) * self.image.get(x0, y1) + fx * self.image.get(x1, y1);
+        (1.0 - fy) * top + fy * bottom
+    }
+}
+
+// Radiance for rays that leave the scene without hitting anything
+#[derive(Clone)]
+pub enum Background {
+    Solid(Color),
+    // Blend from `bottom` straight down to `top` straight up
+    Gradient { bottom: Color, top: Color },
+    Environment(EnvironmentMap),
+}
+
+impl Background {
+    pub fn color(&self, dir: &Vec3) -> Color {
+        match self {
+            Background::Solid(color) => *color,
+            Background::Gradient { bottom, top } => {
+                let unit_direction = Vec3::unit_vector(dir);
+                let a = 0.5 * (unit_direction.y() + 1.0);
+                (1.0 - a) * *bottom + a * *top
+            }
+            Background::Environment(map) => map.lookup(dir),
+        }
+    }
+}
+
+// The white to sky blue blend used before backgrounds were configurable
+impl Default for Background {
+    fn default() -> Self {
+        Background::Gradient {
+            bottom: Color::new(1.0, 1.0, 1.0),
+            top: Color::new(0.5, 0.7, 1.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Color, b: Color) -> bool {
+        (a - b).length() < 1e-9
+    }
+
+    #[test]
+    fn solid_and_gradient() {
+        let solid = Background::Solid(Color::new(0.1, 0.2, 0.3));
+        assert!(close(
+            solid.color(&Vec3::new(1.0, 5.0, 2.0)),
+            Color::new(0.1, 0.2, 0.3)
+        ));
+
+        let sky = Background::default();
+        assert!(close(
+            sky.color(&Vec3::new(0.0, 2.0, 0.0)),
+            Color::new(0.5, 0.7, 1.0)
+        ));
+        assert!(close(
+            sky.color(&Vec3::new(0.0, -1.0, 0.0)),
+            Color::new(1.0, 1.0, 1.0)
+        ));
+    }
+
+    #[test]
+    fn environment_map_orientation() {
+        // Column index in red, blue top row
+        let mut image = Image::new(4, 2);
+        for x in 0..4 {
+            image.set(x, 0, Color::new(x as f64, 0.0, 10.0));
+            image.set(x, 1, Color::new(x as f64, 0.0, 0.0));
+        }
+        let map = EnvironmentMap::new(image.clone(), 0.0, 1.0);
+
+        // Straight up reads the top row, straight down the bottom one
+        assert!(map.lookup(&Vec3::new(0.0, 1.0, 0.0)).z() > 9.9);
+        assert!(map.lookup(&Vec3::new(0.0, -1.0, 0.0)).z() < 0.1);
+
+        // Looking down -z lands between columns 1 and 2, +x a quarter further
+        let ahead = map.lookup(&Vec3::new(0.0, 0.0, -1.0));
+        assert!((ahead.x() - 1.5).abs() < 1e-9);
+        let right = map.lookup(&Vec3::new(1.0, 0.0, 0.0));
+        assert!((right.x() - 2.5).abs() < 1e-9);
+
+        // Rotating by 90 degrees brings what was to the left (-x) in front,
+        // intensity scales everything
+        let turned = EnvironmentMap::new(image, 90.0, 2.0);
+        let ahead = turned.lookup(&Vec3::new(0.0, 0.0, -1.0));
+        assert!((ahead.x() - 1.0).abs() < 1e-9);
+    }
+}
@@ -1,14 +1,19 @@
-use std::io::{self, Write};
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
 
 use log::info;
 
 use crate::{
-    color::{write_color, Color},
+    background::Background,
+    color::Color,
     hittable::{HitRecord, Hittable},
+    image::Image,
     interval::Interval,
     material::Material,
     ray::Ray,
-    utils::{random_f64, INFINITY},
+    utils::{random_f64, seed_thread_rng, INFINITY},
     vec3::{Point3, Vec3},
 };
 
@@ -32,10 +37,78 @@ pub struct Camera {
     // w: Vec3,
     defocus_disk_u: Vec3,
     defocus_disk_v: Vec3,
-    defocus_angle: f64
+    defocus_angle: f64,
+    shutter_open: f64,
+    shutter_close: f64,
+    background: Background,
+    threads: usize,
+    seed: Option<u64>,
+}
+
+// User-facing camera parameters, turned into a Camera with build
+#[derive(Clone)]
+pub struct CameraSettings {
+    pub aspect_ratio: f64,
+    pub image_width: i32,
+    // Exact height in pixels, instead of the one the aspect ratio gives
+    pub image_height: Option<i32>,
+    pub samples_per_pixel: i32,
+    pub recursion_depth: usize,
+    pub vfov: f64,
+    pub look_from: Point3,
+    pub look_at: Point3,
+    pub vup: Vec3,
+    pub focus_dist: f64,
+    pub defocus_angle: f64,
+    // Rays are spread over this time span, moving objects blur across it
+    pub shutter_open: f64,
+    pub shutter_close: f64,
+}
+
+impl CameraSettings {
+    pub fn build(&self) -> Camera {
+        let image_height = self
+            .image_height
+            .unwrap_or_else(|| Camera::height_for(self.aspect_ratio, self.image_width));
+        let mut camera = Camera::with_size(
+            self.image_width,
+            image_height,
+            self.samples_per_pixel,
+            self.recursion_depth,
+            self.vfov,
+            self.look_from,
+            self.look_at,
+            self.vup,
+            self.focus_dist,
+            self.defocus_angle,
+        );
+        camera.set_shutter(self.shutter_open, self.shutter_close);
+        camera
+    }
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        CameraSettings {
+            aspect_ratio: 16.0 / 9.0,
+            image_width: 1200,
+            image_height: None,
+            samples_per_pixel: 500,
+            recursion_depth: 50,
+            vfov: 20.0,
+            look_from: Point3::new(1.0, 2.0, 1.0),
+            look_at: Point3::new(0.0, 0.0, -1.0),
+            vup: Vec3::new(0.0, 1.0, 0.0),
+            focus_dist: 10.0,
+            defocus_angle: 0.6,
+            shutter_open: 0.0,
+            shutter_close: 0.0,
+        }
+    }
 }
 
 impl Camera {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         aspect_ratio: f64,
         image_width: i32,
@@ -46,13 +119,49 @@ impl Camera {
         look_at: Vec3,
         vup: Vec3,
         focus_dist: f64,
-        defocus_angle: f64
+        defocus_angle: f64,
     ) -> Self {
+        let image_height = Camera::height_for(aspect_ratio, image_width);
+        Camera::with_size(
+            image_width,
+            image_height,
+            samples_per_pixel,
+            recursion_depth,
+            vfov,
+            look_from,
+            look_at,
+            vup,
+            focus_dist,
+            defocus_angle,
+        )
+    }
+
+    // Image height for a width and aspect ratio, at least one pixel
+    fn height_for(aspect_ratio: f64, image_width: i32) -> i32 {
         let image_height = (image_width as f64 / aspect_ratio) as i32;
-        let image_height = if image_height < 1 { 1 } else { image_height };
+        if image_height < 1 {
+            1
+        } else {
+            image_height
+        }
+    }
 
+    // Like new, with the height given in pixels rather than by an aspect ratio
+    #[allow(clippy::too_many_arguments)]
+    pub fn with_size(
+        image_width: i32,
+        image_height: i32,
+        samples_per_pixel: i32,
+        recursion_depth: usize,
+        vfov: f64,
+        look_from: Vec3,
+        look_at: Vec3,
+        vup: Vec3,
+        focus_dist: f64,
+        defocus_angle: f64,
+    ) -> Self {
         let theta = f64::to_radians(vfov);
-        let h = f64::tan(theta/2.0);
+        let h = f64::tan(theta / 2.0);
         let viewport_height = 2.0 * h * focus_dist;
         let viewport_width = viewport_height * (f64::from(image_width) / f64::from(image_height));
 
@@ -74,7 +183,8 @@ impl Camera {
         let pixel_delta_v = viewport_v / image_height as f64;
 
         // Upper left pixel
-        let viewport_upper_left = camera_center - (focus_dist * w) - viewport_u / 2.0 - viewport_v / 2.0;
+        let viewport_upper_left =
+            camera_center - (focus_dist * w) - viewport_u / 2.0 - viewport_v / 2.0;
         let pixel_00 = viewport_upper_left + 0.5 * (pixel_delta_u + pixel_delta_v);
 
         let defocus_radius = focus_dist * f64::tan(f64::to_radians(defocus_angle / 2.0));
@@ -101,11 +211,46 @@ impl Camera {
             // v,
             defocus_disk_u,
             defocus_disk_v,
-            defocus_angle
+            defocus_angle,
+            shutter_open: 0.0,
+            shutter_close: 0.0,
+            background: Background::default(),
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            seed: None,
         }
     }
 
-    fn ray_color<T: Hittable>(r: &Ray, world: &T, depth: usize) -> Color {
+    pub fn image_width(&self) -> i32 {
+        self.image_width
+    }
+
+    pub fn image_height(&self) -> i32 {
+        self.image_height
+    }
+
+    // Number of worker threads used by render, at least one
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = usize::max(1, threads);
+    }
+
+    // Time span the rays of each pixel are spread over, a closed shutter
+    // (open == close) gives a sharp image at that instant
+    pub fn set_shutter(&mut self, open: f64, close: f64) {
+        self.shutter_open = open;
+        self.shutter_close = f64::max(open, close);
+    }
+
+    // What rays that miss everything see
+    pub fn set_background(&mut self, background: Background) {
+        self.background = background;
+    }
+
+    // Makes render deterministic: every scanline reseeds its thread's generator
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = Some(seed);
+    }
+
+    fn ray_color<T: Hittable + ?Sized>(&self, r: &Ray, world: &T, depth: usize) -> Color {
         // Limiting ray bounces
         if depth == 0 {
             return Color::new(0.0, 0.0, 0.0);
@@ -116,16 +261,16 @@ impl Camera {
         let interval = Interval::new(0.001, INFINITY);
         let rec = world.hit(r, &interval, &mut rec);
         if rec.is_hit {
+            let emitted = rec.mat.emitted(rec);
             let scatter = rec.mat.scatter(r, rec);
             if scatter.is_scattered {
-                return scatter.attenuation * Camera::ray_color(&scatter.ray, world, depth - 1);
+                return emitted
+                    + scatter.attenuation * self.ray_color(&scatter.ray, world, depth - 1);
             }
-            return Color::new(0.0, 0.0, 0.0);
+            return emitted;
         }
 
-        let unit_direction = Color::unit_vector(r.dir());
-        let a = 0.5 * (unit_direction.y() + 1.0);
-        (1.0 - a) * Color::new(1.0, 1.0, 1.0) + a * Color::new(0.5, 0.7, 1.0)
+        self.background.color(r.dir())
     }
 
     fn sample_square() -> Vec3 {
@@ -142,27 +287,157 @@ impl Camera {
         let pixel_sample = self.pixel_00
             + ((i as f64 + offset.x()) * self.pixel_delta_u)
             + ((j as f64 + offset.y()) * self.pixel_delta_v);
-        let ray_origin = if self.defocus_angle <= 0.0 { self.camera_center } else { self.defocus_disk_sample() };
+        let ray_origin = if self.defocus_angle <= 0.0 {
+            self.camera_center
+        } else {
+            self.defocus_disk_sample()
+        };
         let ray_direction = pixel_sample - ray_origin;
-        Ray::new(ray_origin, ray_direction)
+        let ray_time = if self.shutter_close > self.shutter_open {
+            self.shutter_open + random_f64() * (self.shutter_close - self.shutter_open)
+        } else {
+            self.shutter_open
+        };
+        Ray::with_time(ray_origin, ray_direction, ray_time)
     }
 
-    pub fn render<T: Hittable>(&self, world: &T) {
-        let mut stdout = io::stdout();
-        let header = format!("P3\n{} {}\n255\n", self.image_width, self.image_height);
-        stdout.write_all(header.as_bytes()).unwrap();
-
-        for j in 0..self.image_height {
-            info!("Scan lines remaining: {} ", self.image_height - j);
-            for i in 0..self.image_width {
+    fn render_scanline<T: Hittable + ?Sized>(&self, world: &T, j: i32) -> Vec<Color> {
+        if let Some(seed) = self.seed {
+            seed_thread_rng(seed ^ (j as u64 + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+        }
+        (0..self.image_width)
+            .map(|i| {
                 let mut pixel_colour = Color::new(0.0, 0.0, 0.0);
                 for _ in 0..self.samples_per_pixel {
                     let ray = self.get_ray(i, j);
-                    pixel_colour += Camera::ray_color(&ray, world, self.recursion_depth);
+                    pixel_colour += self.ray_color(&ray, world, self.recursion_depth);
+                }
+                self.pixels_samples_scale * pixel_colour
+            })
+            .collect()
+    }
+
+    pub fn render<T: Hittable + ?Sized>(&self, world: &T) -> Image {
+        let height = self.image_height as usize;
+        let next_row = AtomicUsize::new(0);
+
+        // Workers pull scanlines off a shared counter and tag each with its row
+        // so the image can be assembled in order regardless of finish order
+        let mut rows: Vec<Vec<Color>> = vec![Vec::new(); height];
+        thread::scope(|s| {
+            let workers: Vec<_> = (0..usize::min(self.threads, height))
+                .map(|_| {
+                    s.spawn(|| {
+                        let mut done = Vec::new();
+                        loop {
+                            let j = next_row.fetch_add(1, Ordering::Relaxed);
+                            if j >= height {
+                                break;
+                            }
+                            info!("Scan lines remaining: {} ", height - j);
+                            done.push((j, self.render_scanline(world, j as i32)));
+                        }
+                        done
+                    })
+                })
+                .collect();
+
+            for worker in workers {
+                for (j, row) in worker.join().unwrap() {
+                    rows[j] = row;
                 }
-                write_color(&mut stdout, &(self.pixels_samples_scale * pixel_colour));
             }
-        }
+        });
+
         info!("Done!");
+        Image::from_pixels(
+            self.image_width as usize,
+            height,
+            rows.into_iter().flatten().collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        material::MaterialType,
+        objects::{hittable_list::HittableList, sphere::Sphere},
+    };
+
+    #[test]
+    fn render_fills_framebuffer() {
+        let mut camera = Camera::new(
+            2.0,
+            16,
+            4,
+            5,
+            90.0,
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            1.0,
+            0.0,
+        );
+        camera.set_threads(3);
+        let world = HittableList::new();
+        let image = camera.render(&world);
+
+        assert_eq!(image.width(), 16);
+        assert_eq!(image.height(), 8);
+        // Empty world shows only the sky gradient, bluer towards the top
+        let top = image.get(0, 0);
+        let bottom = image.get(0, 7);
+        assert!(top.x() < bottom.x());
+        assert!(image.pixels().iter().all(|c| c.z() > 0.0));
+    }
+
+    #[test]
+    fn lights_add_emission() {
+        let settings = CameraSettings {
+            image_width: 4,
+            samples_per_pixel: 2,
+            ..CameraSettings::default()
+        };
+        // Camera inside a glowing sphere sees nothing but its emission
+        let mut world = HittableList::new();
+        world.add_obj(Sphere::new(
+            Point3::default(),
+            100.0,
+            MaterialType::diffuse_light(Color::new(2.0, 1.0, 0.5)),
+        ));
+        let image = settings.build().render(&world);
+        for p in image.pixels() {
+            assert_eq!((p.x(), p.y(), p.z()), (2.0, 1.0, 0.5));
+        }
+    }
+
+    #[test]
+    fn seeded_render_is_reproducible() {
+        let settings = CameraSettings {
+            image_width: 12,
+            samples_per_pixel: 3,
+            recursion_depth: 4,
+            ..CameraSettings::default()
+        };
+        let mut world = HittableList::new();
+        world.add_obj(Sphere::new(
+            Point3::new(0.0, 0.0, -1.0),
+            0.5,
+            MaterialType::lambertian(Color::new(0.5, 0.5, 0.5)),
+        ));
+
+        let render = |threads: usize| {
+            let mut camera = settings.build();
+            camera.set_threads(threads);
+            camera.set_seed(7);
+            camera.render(&world)
+        };
+        let a = render(1);
+        let b = render(4);
+        for (p, q) in a.pixels().iter().zip(b.pixels()) {
+            assert_eq!((p.x(), p.y(), p.z()), (q.x(), q.y(), q.z()));
+        }
     }
 }
@@ -0,0 +1,240 @@
+use std::path::PathBuf;
+
+use ray_tracer::{camera::CameraSettings, output::OutputFormat};
+
+pub const USAGE: &str = "\
+Usage: ray-tracer [OPTIONS]
+
+Options:
+  -s, --scene <PATH>       Scene description file (defaults to the built-in scene)
+  -o, --output <PATH>      Output image path (defaults to ASCII PPM on stdout)
+  -f, --format <FORMAT>    p3, p6, pfm, hdr, png, exr, exr-float (defaults to the output extension)
+  -W, --width <PIXELS>     Image width
+  -H, --height <PIXELS>    Image height, sets the aspect ratio together with the width
+      --samples <N>        Samples per pixel
+      --depth <N>          Maximum ray bounces
+  -j, --threads <N>        Worker threads (defaults to the available cores)
+      --seed <N>           Random seed for reproducible renders
+  -h, --help               Print this help
+";
+
+#[derive(Default)]
+pub struct Options {
+    pub scene: Option<PathBuf>,
+    pub output: Option<PathBuf>,
+    pub format: Option<OutputFormat>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub samples: Option<i32>,
+    pub depth: Option<usize>,
+    pub threads: Option<usize>,
+    pub seed: Option<u64>,
+}
+
+pub enum Command {
+    Render(Options),
+    Help,
+}
+
+fn positive<T: std::str::FromStr + PartialOrd + Default>(
+    flag: &str,
+    value: &str,
+) -> Result<T, String> {
+    match value.parse::<T>() {
+        Ok(v) if v > T::default() => Ok(v),
+        _ => Err(format!(
+            "{} expects a positive integer, found '{}'",
+            flag, value
+        )),
+    }
+}
+
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Command, String> {
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        // Accept both "--flag value" and "--flag=value"
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if arg.starts_with("--") => {
+                (flag.to_string(), Some(value.to_string()))
+            }
+            _ => (arg.clone(), None),
+        };
+        if flag == "-h" || flag == "--help" {
+            return Ok(Command::Help);
+        }
+
+        let mut value = || {
+            inline
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("{} expects a value", flag))
+        };
+        match flag.as_str() {
+            "-s" | "--scene" => options.scene = Some(PathBuf::from(value()?)),
+            "-o" | "--output" => options.output = Some(PathBuf::from(value()?)),
+            "-f" | "--format" => {
+                let name = value()?;
+                let format = OutputFormat::from_name(&name)
+                    .ok_or_else(|| format!("unknown output format '{}'", name))?;
+                options.format = Some(format);
+            }
+            "-W" | "--width" => options.width = Some(positive(&flag, &value()?)?),
+            "-H" | "--height" => options.height = Some(positive(&flag, &value()?)?),
+            "--samples" => options.samples = Some(positive(&flag, &value()?)?),
+            "--depth" => options.depth = Some(positive(&flag, &value()?)?),
+            "-j" | "--threads" => options.threads = Some(positive(&flag, &value()?)?),
+            "--seed" => {
+                let seed = value()?;
+                let seed = seed
+                    .parse()
+                    .map_err(|_| format!("--seed expects an unsigned integer, found '{}'", seed))?;
+                options.seed = Some(seed);
+            }
+            _ => return Err(format!("unknown argument '{}'", arg)),
+        }
+    }
+
+    Ok(Command::Render(options))
+}
+
+impl Options {
+    // Explicit --format wins, then the output extension, then P3 for stdout
+    pub fn output_format(&self) -> Result<OutputFormat, String> {
+        match (&self.format, &self.output) {
+            (Some(format), _) => Ok(*format),
+            (None, Some(path)) => OutputFormat::from_path(path).ok_or_else(|| {
+                format!(
+                    "cannot tell the format of '{}', pass --format",
+                    path.display()
+                )
+            }),
+            (None, None) => Ok(OutputFormat::PpmAscii),
+        }
+    }
+
+    // Command line settings override the scene's camera
+    pub fn apply(&self, camera: &mut CameraSettings) {
+        match (self.width, self.height) {
+            (Some(width), Some(height)) => {
+                camera.image_width = width;
+                camera.image_height = Some(height);
+                camera.aspect_ratio = width as f64 / height as f64;
+            }
+            (Some(width), None) => camera.image_width = width,
+            (None, Some(height)) => {
+                camera.image_width =
+                    i32::max(1, (height as f64 * camera.aspect_ratio).round() as i32);
+                camera.image_height = Some(height);
+            }
+            (None, None) => {}
+        }
+        if let Some(samples) = self.samples {
+            camera.samples_per_pixel = samples;
+        }
+        if let Some(depth) = self.depth {
+            camera.recursion_depth = depth;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        match parse_args(args.iter().map(|a| a.to_string()))? {
+            Command::Render(options) => Ok(options),
+            Command::Help => Err("help".to_string()),
+        }
+    }
+
+    #[test]
+    fn parses_render_settings() {
+        let options = parse(&[
+            "--scene",
+            "a.scene",
+            "-o",
+            "out.png",
+            "-W",
+            "640",
+            "--height=360",
+            "--samples",
+            "16",
+            "--depth",
+            "8",
+            "-j",
+            "4",
+            "--seed",
+            "42",
+        ])
+        .unwrap();
+        assert_eq!(options.scene, Some(PathBuf::from("a.scene")));
+        assert_eq!(options.output_format(), Ok(OutputFormat::Png));
+        assert_eq!(options.threads, Some(4));
+        assert_eq!(options.seed, Some(42));
+
+        let mut camera = CameraSettings::default();
+        options.apply(&mut camera);
+        assert_eq!(camera.image_width, 640);
+        assert_eq!(camera.samples_per_pixel, 16);
+        assert_eq!(camera.recursion_depth, 8);
+        assert_eq!(camera.build().image_height(), 360);
+
+        // Sizes the aspect ratio alone would round down a pixel
+        let options = parse(&["-W", "800", "-H", "127"]).unwrap();
+        options.apply(&mut camera);
+        assert_eq!(camera.build().image_height(), 127);
+
+        // A height alone keeps the scene's aspect ratio
+        let options = parse(&["-H", "103"]).unwrap();
+        let mut camera = CameraSettings::default();
+        options.apply(&mut camera);
+        let camera = camera.build();
+        assert_eq!((camera.image_width(), camera.image_height()), (183, 103));
+    }
+
+    #[test]
+    fn format_overrides_extension() {
+        let options = parse(&["-o", "render.bin", "--format", "exr-float"]).unwrap();
+        assert!(matches!(options.output_format(), Ok(OutputFormat::Exr(_))));
+        assert_eq!(
+            parse(&["-o", "sky.HDR"]).unwrap().output_format(),
+            Ok(OutputFormat::Hdr)
+        );
+        assert!(parse(&["-o", "render.bin"])
+            .unwrap()
+            .output_format()
+            .is_err());
+        assert_eq!(
+            parse(&[]).unwrap().output_format(),
+            Ok(OutputFormat::PpmAscii)
+        );
+    }
+
+    #[test]
+    fn help_and_errors() {
+        assert_eq!(
+            parse(&["--samples", "4", "--help"]).err(),
+            Some("help".to_string())
+        );
+        assert_eq!(
+            parse(&["--samples", "0"]).err(),
+            Some("--samples expects a positive integer, found '0'".to_string())
+        );
+        assert_eq!(
+            parse(&["--width"]).err(),
+            Some("--width expects a value".to_string())
+        );
+        assert_eq!(
+            parse(&["--bogus"]).err(),
+            Some("unknown argument '--bogus'".to_string())
+        );
+        assert_eq!(
+            parse(&["-f", "gif"]).err(),
+            Some("unknown output format 'gif'".to_string())
+        );
+        assert!(parse(&["--seed", "-1"]).is_err());
+    }
+}
@@ -1,9 +1,18 @@
-use std::io::Write;
+use std::io::{self, Write};
 
 use crate::{interval::Interval, vec3::Vec3};
 
 pub type Color = Vec3;
 
+// Inverse of linear_to_gamma, for 8-bit colours read from files
+pub fn gamma_to_linear(gamma_component: f64) -> f64 {
+    if gamma_component > 0.0 {
+        gamma_component * gamma_component
+    } else {
+        0.0
+    }
+}
+
 pub fn linear_to_gamma(linear_component: f64) -> f64 {
     if linear_component > 0.0 {
         f64::sqrt(linear_component)
@@ -12,7 +21,8 @@ pub fn linear_to_gamma(linear_component: f64) -> f64 {
     }
 }
 
-pub fn write_color<T: Write>(stdout: &mut T, pixel_color: &Color) {
+// Gamma corrected, clamped and quantized 8-bit RGB
+pub fn to_rgb_bytes(pixel_color: &Color) -> [u8; 3] {
     let r = pixel_color.x();
     let g = pixel_color.y();
     let b = pixel_color.z();
@@ -23,11 +33,14 @@ pub fn write_color<T: Write>(stdout: &mut T, pixel_color: &Color) {
     let b = linear_to_gamma(b);
 
     let intensity = Interval::new(0.000, 0.999);
-    let rbyte = (256.0 * intensity.clamp(r)) as i32;
-    let gbyte = (256.0 * intensity.clamp(g)) as i32;
-    let bbyte = (256.0 * intensity.clamp(b)) as i32;
+    let rbyte = (256.0 * intensity.clamp(r)) as u8;
+    let gbyte = (256.0 * intensity.clamp(g)) as u8;
+    let bbyte = (256.0 * intensity.clamp(b)) as u8;
+
+    [rbyte, gbyte, bbyte]
+}
 
-    stdout
-        .write_all(format!("{} {} {}\n", rbyte, gbyte, bbyte).as_bytes())
-        .unwrap();
+pub fn write_color<T: Write>(out: &mut T, pixel_color: &Color) -> io::Result<()> {
+    let [rbyte, gbyte, bbyte] = to_rgb_bytes(pixel_color);
+    out.write_all(format!("{} {} {}\n", rbyte, gbyte, bbyte).as_bytes())
 }
@@ -1,4 +1,7 @@
+use std::sync::Arc;
+
 use crate::{
+    aabb::Aabb,
     color::Color,
     interval::Interval,
     material::MaterialType,
@@ -12,8 +15,17 @@ pub struct HitRecord {
     pub normal: Vec3,
     pub t: f64,
     pub front_face: bool,
+    // Surface coordinates, within [0, 1] on bounded shapes
+    pub u: f64,
+    pub v: f64,
+    // Derivatives of p along u and v, spanning the tangent plane. Normal and
+    // bump maps build their shading frame from them.
+    pub dpdu: Vec3,
+    pub dpdv: Vec3,
     pub is_hit: bool,
     pub mat: MaterialType,
+    // Interpolated per-vertex colour, for meshes that carry one
+    pub vertex_color: Option<Color>,
 }
 
 impl HitRecord {
@@ -38,12 +50,39 @@ impl Default for HitRecord {
             normal,
             t: 0.0,
             front_face: false,
+            u: 0.0,
+            v: 0.0,
+            dpdu: Vec3::default(),
+            dpdv: Vec3::default(),
             is_hit: false,
             mat,
+            vertex_color: None,
         }
     }
 }
 
-pub trait Hittable {
+pub trait Hittable: Send + Sync {
     fn hit<'a>(&self, r: &Ray, ray_t: &Interval, rec: &'a mut HitRecord) -> &'a mut HitRecord;
+    fn bounding_box(&self) -> Aabb;
+}
+
+impl<H: Hittable + ?Sized> Hittable for Box<H> {
+    fn hit<'a>(&self, r: &Ray, ray_t: &Interval, rec: &'a mut HitRecord) -> &'a mut HitRecord {
+        (**self).hit(r, ray_t, rec)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        (**self).bounding_box()
+    }
+}
+
+// Shared objects, so one mesh can back many instances
+impl<H: Hittable + ?Sized> Hittable for Arc<H> {
+    fn hit<'a>(&self, r: &Ray, ray_t: &Interval, rec: &'a mut HitRecord) -> &'a mut HitRecord {
+        (**self).hit(r, ray_t, rec)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        (**self).bounding_box()
+    }
 }
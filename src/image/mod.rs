@@ -0,0 +1,72 @@
+use crate::color::Color;
+
+// Linear HDR framebuffer, pixels stored row-major from the top-left
+#[derive(Clone)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize) -> Self {
+        Image {
+            width,
+            height,
+            pixels: vec![Color::default(); width * height],
+        }
+    }
+
+    pub fn from_pixels(width: usize, height: usize, pixels: Vec<Color>) -> Self {
+        assert_eq!(
+            pixels.len(),
+            width * height,
+            "pixel count does not match {}x{} image",
+            width,
+            height
+        );
+        Image {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[Color] {
+        &self.pixels
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Color {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, color: Color) {
+        self.pixels[y * self.width + x] = color;
+    }
+
+    pub fn rows(&self) -> std::slice::Chunks<'_, Color> {
+        self.pixels.chunks(usize::max(1, self.width))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn row_major_layout() {
+        let mut image = Image::new(3, 2);
+        image.set(2, 1, Color::new(1.0, 2.0, 3.0));
+        assert_eq!(image.pixels()[5].z(), 3.0);
+        assert_eq!(image.get(2, 1).x(), 1.0);
+        assert_eq!(image.rows().count(), 2);
+    }
+}
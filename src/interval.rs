@@ -1,5 +1,6 @@
 use crate::utils::INFINITY;
 
+#[derive(Clone, Copy)]
 pub struct Interval {
     pub min: f64,
     pub max: f64,
@@ -10,6 +11,14 @@ impl Interval {
         Interval { min, max }
     }
 
+    // Tightest interval enclosing both inputs
+    pub fn enclose(a: &Interval, b: &Interval) -> Self {
+        Interval {
+            min: f64::min(a.min, b.min),
+            max: f64::max(a.max, b.max),
+        }
+    }
+
     pub fn size(&self) -> f64 {
         self.max - self.min
     }
@@ -31,6 +40,11 @@ impl Interval {
         };
         x
     }
+
+    pub fn expand(&self, delta: f64) -> Interval {
+        let padding = delta / 2.0;
+        Interval::new(self.min - padding, self.max + padding)
+    }
 }
 
 impl Default for Interval {
@@ -0,0 +1,17 @@
+pub mod aabb;
+pub mod background;
+pub mod camera;
+pub mod color;
+pub mod hittable;
+pub mod image;
+pub mod interval;
+pub mod loaders;
+pub mod material;
+pub mod objects;
+pub mod output;
+pub mod polynomial;
+pub mod ray;
+pub mod scene;
+pub mod texture;
+pub mod utils;
+pub mod vec3;
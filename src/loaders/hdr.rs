@@ -0,0 +1,270 @@
+use crate::{
+    color::Color,
+    image::Image,
+    output::hdr::{MAX_RLE_WIDTH, MIN_RLE_WIDTH},
+};
+
+use super::LoadError;
+
+// Most pixels four bytes of a scanline can stand for, one repeat marker in
+// the original encoding. RLE scanlines need two bytes per channel for every
+// 127 pixels. Only chains of repeat markers go further, and those are
+// rejected as too large.
+const MAX_PIXELS_PER_FOUR_BYTES: usize = 255;
+
+// Mantissas are not offset to the middle of their bucket, so channels
+// stored as zero stay black
+pub fn from_rgbe(rgbe: [u8; 4]) -> Color {
+    if rgbe[3] == 0 {
+        return Color::new(0.0, 0.0, 0.0);
+    }
+    let f = 2f64.powi(rgbe[3] as i32 - (128 + 8));
+    Color::new(rgbe[0] as f64 * f, rgbe[1] as f64 * f, rgbe[2] as f64 * f)
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn line(&mut self) -> Option<&'a str> {
+        let rest = self.bytes.get(self.pos..)?;
+        let end = rest.iter().position(|&b| b == b'\n')?;
+        self.pos += end + 1;
+        std::str::from_utf8(&rest[..end]).ok()
+    }
+
+    fn byte(&mut self) -> Option<u8> {
+        let b = *self.bytes.get(self.pos)?;
+        self.pos += 1;
+        Some(b)
+    }
+
+    fn pixel(&mut self) -> Option<[u8; 4]> {
+        let p = self.bytes.get(self.pos..self.pos + 4)?;
+        self.pos += 4;
+        Some([p[0], p[1], p[2], p[3]])
+    }
+
+    fn peek(&self, count: usize) -> Option<&'a [u8]> {
+        self.bytes.get(self.pos..self.pos + count)
+    }
+}
+
+fn invalid(message: &str) -> LoadError {
+    LoadError::Invalid(format!("HDR: {}", message))
+}
+
+// Reads RGBE files with the standard -Y/+X layout, or +Y for bottom-up
+// images. Both the per-channel and the original whole-pixel run-length
+// encodings are understood.
+pub fn read_hdr(bytes: &[u8]) -> Result<Image, LoadError> {
+    let mut reader = Reader { bytes, pos: 0 };
+    let truncated = || invalid("truncated header");
+
+    let magic = reader.line().ok_or_else(truncated)?;
+    if !magic.starts_with("#?") {
+        return Err(invalid("missing #? signature"));
+    }
+    let mut exposure = 1.0;
+    loop {
+        let line = reader.line().ok_or_else(truncated)?.trim_end_matches('\r');
+        if line.is_empty() {
+            break;
+        }
+        if let Some(format) = line.strip_prefix("FORMAT=") {
+            if format != "32-bit_rle_rgbe" {
+                return Err(invalid(&format!("unsupported format '{}'", format)));
+            }
+        } else if let Some(value) = line.strip_prefix("EXPOSURE=") {
+            let value: f64 = value.trim().parse().map_err(|_| invalid("bad exposure"))?;
+            exposure *= value;
+        }
+    }
+
+    let resolution = reader.line().ok_or_else(truncated)?;
+    let fields: Vec<&str> = resolution.split_whitespace().collect();
+    let (top_down, height, width) = match fields[..] {
+        [y, h, "+X", w] if y == "-Y" || y == "+Y" => (y == "-Y", h, w),
+        _ => {
+            return Err(invalid(&format!(
+                "unsupported resolution line '{}'",
+                resolution
+            )))
+        }
+    };
+    let width: usize = width.parse().map_err(|_| invalid("bad width"))?;
+    let height: usize = height.parse().map_err(|_| invalid("bad height"))?;
+    if exposure <= 0.0 || !f64::is_finite(exposure) {
+        return Err(invalid("bad exposure"));
+    }
+
+    // Check the size against the data before allocating anything
+    let row_bytes = 4 * width.div_ceil(MAX_PIXELS_PER_FOUR_BYTES);
+    match width.checked_mul(height).and(height.checked_mul(row_bytes)) {
+        Some(needed) if needed <= bytes.len() - reader.pos => {}
+        _ => return Err(invalid("image too large")),
+    }
+
+    let mut image = Image::new(width, height);
+    for row in 0..height {
+        let scanline = read_scanline(&mut reader, width)
+            .ok_or_else(|| invalid(&format!("truncated data in scanline {}", row)))??;
+        let y = if top_down { row } else { height - 1 - row };
+        for (x, &rgbe) in scanline.iter().enumerate() {
+            image.set(x, y, from_rgbe(rgbe) / exposure);
+        }
+    }
+    Ok(image)
+}
+
+// None when the data runs out, an error for malformed runs
+fn read_scanline(reader: &mut Reader, width: usize) -> Option<Result<Vec<[u8; 4]>, LoadError>> {
+    let rle = (MIN_RLE_WIDTH..=MAX_RLE_WIDTH).contains(&width)
+        && matches!(reader.peek(4)?, [2, 2, hi, _] if hi & 0x80 == 0);
+    if !rle {
+        return read_flat(reader, width);
+    }
+
+    let header = reader.pixel()?;
+    if ((header[2] as usize) << 8 | header[3] as usize) != width {
+        return Some(Err(invalid("scanline width mismatch")));
+    }
+    let mut scanline = vec![[0u8; 4]; width];
+    for channel in 0..4 {
+        let mut x = 0;
+        while x < width {
+            let count = reader.byte()? as usize;
+            let (len, run) = if count > 128 {
+                (count - 128, true)
+            } else {
+                (count, false)
+            };
+            if len == 0 || x + len > width {
+                return Some(Err(invalid("bad run length")));
+            }
+            if run {
+                let value = reader.byte()?;
+                for pixel in &mut scanline[x..x + len] {
+                    pixel[channel] = value;
+                }
+            } else {
+                for pixel in &mut scanline[x..x + len] {
+                    pixel[channel] = reader.byte()?;
+                }
+            }
+            x += len;
+        }
+    }
+    Some(Ok(scanline))
+}
+
+// Original encoding: plain pixels, where (1, 1, 1, n) repeats the previous
+// pixel n times, shifted up a byte for each consecutive repeat marker
+fn read_flat(reader: &mut Reader, width: usize) -> Option<Result<Vec<[u8; 4]>, LoadError>> {
+    let mut scanline: Vec<[u8; 4]> = Vec::with_capacity(width);
+    let mut shift = 0;
+    while scanline.len() < width {
+        let pixel = reader.pixel()?;
+        if let [1, 1, 1, n] = pixel {
+            let Some(&previous) = scanline.last() else {
+                return Some(Err(invalid("repeat marker at start of scanline")));
+            };
+            let count = (n as usize).checked_shl(shift).unwrap_or(usize::MAX);
+            if count > width - scanline.len() {
+                return Some(Err(invalid("bad run length")));
+            }
+            scanline.extend(std::iter::repeat_n(previous, count));
+            shift += 8;
+        } else {
+            scanline.push(pixel);
+            shift = 0;
+        }
+    }
+    Some(Ok(scanline))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::hdr::{to_rgbe, write_hdr};
+
+    fn close(a: Color, b: Color, tolerance: f64) -> bool {
+        (a - b).length() <= tolerance * b.length().max(1e-3)
+    }
+
+    #[test]
+    fn rgbe_roundtrip() {
+        assert_eq!(from_rgbe([0, 0, 0, 0]).length(), 0.0);
+        for c in [
+            Color::new(1.0, 0.5, 0.25),
+            Color::new(1500.0, 20.0, 0.3),
+            Color::new(1e-4, 2e-4, 3e-4),
+        ] {
+            let back = from_rgbe(to_rgbe(&c));
+            assert!(close(back, c, 0.01));
+        }
+    }
+
+    #[test]
+    fn reads_written_rle_scanlines() {
+        let mut image = Image::new(40, 3);
+        for y in 0..3 {
+            for x in 0..40 {
+                // Flat areas become runs, the gradient literals
+                let value = if x < 20 {
+                    0.5
+                } else {
+                    x as f64 * 0.1 + y as f64
+                };
+                image.set(x, y, Color::new(value, 2.0 * value, 3.0));
+            }
+        }
+        let mut out = Vec::new();
+        write_hdr(&mut out, &image).unwrap();
+
+        let back = read_hdr(&out).unwrap();
+        assert_eq!((back.width(), back.height()), (40, 3));
+        for y in 0..3 {
+            for x in 0..40 {
+                assert!(close(back.get(x, y), image.get(x, y), 0.01));
+            }
+        }
+    }
+
+    #[test]
+    fn reads_flat_and_old_style_runs() {
+        // Too narrow for RLE, stored bottom-up with an exposure
+        let mut bytes = b"#?RGBE\nEXPOSURE=2\n\n+Y 2 +X 3\n".to_vec();
+        bytes.extend([128, 0, 0, 129, 1, 1, 1, 2]);
+        bytes.extend([0, 128, 0, 129, 0, 0, 128, 129, 0, 0, 0, 0]);
+        let image = read_hdr(&bytes).unwrap();
+        assert!(close(image.get(2, 1), Color::new(0.5, 0.0, 0.0), 1e-9));
+        assert!(close(image.get(0, 0), Color::new(0.0, 0.5, 0.0), 1e-9));
+        assert_eq!(image.get(2, 0).x(), 0.0);
+
+        let err = read_hdr(&bytes[..bytes.len() - 1]).err().unwrap();
+        assert_eq!(err.to_string(), "HDR: truncated data in scanline 1");
+        let err = read_hdr(b"#?RADIANCE\nFORMAT=32-bit_rle_xyze\n\n-Y 1 +X 1\n")
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "HDR: unsupported format '32-bit_rle_xyze'");
+        assert!(read_hdr(b"#?RADIANCE\n\n+X 1 -Y 1\n").is_err());
+        for size in ["4294967296 +X 4294967296", "200000 +X 200000"] {
+            let header = format!("#?RADIANCE\n\n-Y {}\n", size);
+            let err = read_hdr(header.as_bytes()).err().unwrap();
+            assert_eq!(err.to_string(), "HDR: image too large");
+        }
+
+        // Each repeat marker in a row shifts the count a further byte up,
+        // empty ones included
+        let mut bytes = b"#?RADIANCE\n\n-Y 1 +X 3\n".to_vec();
+        bytes.extend([128, 0, 0, 129]);
+        for _ in 0..9 {
+            bytes.extend([1, 1, 1, 0]);
+        }
+        let err = read_hdr(&bytes).err().unwrap();
+        assert_eq!(err.to_string(), "HDR: bad run length");
+    }
+}
@@ -0,0 +1,198 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    color::{gamma_to_linear, Color},
+    image::Image,
+    material::MaterialType,
+    objects::{hittable_list::HittableList, mesh::TriangleMesh},
+    vec3::Vec3,
+};
+
+use hdr::read_hdr;
+use pfm::read_pfm;
+use png::read_png;
+use ppm::read_ppm;
+
+pub mod hdr;
+pub mod obj;
+pub mod pfm;
+pub mod ply;
+pub mod png;
+pub mod ppm;
+pub mod stl;
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    Parse {
+        line: usize,
+        message: String,
+    },
+    Invalid(String),
+    File {
+        path: PathBuf,
+        error: Box<LoadError>,
+    },
+}
+
+impl LoadError {
+    // Attach the file an error came from
+    pub fn in_file(self, path: &Path) -> LoadError {
+        LoadError::File {
+            path: path.to_path_buf(),
+            error: Box::new(self),
+        }
+    }
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(e) => write!(f, "{}", e),
+            LoadError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+            LoadError::Invalid(message) => write!(f, "{}", message),
+            LoadError::File { path, error } => write!(f, "{}: {}", path.display(), error),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl From<io::Error> for LoadError {
+    fn from(e: io::Error) -> Self {
+        LoadError::Io(e)
+    }
+}
+
+// Whitespace separated tokens tagged with their line number
+pub(crate) struct Tokens<'a> {
+    iter: Box<dyn Iterator<Item = (usize, &'a str)> + 'a>,
+    line: usize,
+}
+
+impl<'a> Tokens<'a> {
+    // `first_line` is the line number `source` starts at within its file
+    pub(crate) fn new(source: &'a str, first_line: usize) -> Self {
+        let iter = source.lines().enumerate().flat_map(move |(index, line)| {
+            line.split_whitespace()
+                .map(move |token| (first_line + index, token))
+        });
+        Tokens {
+            iter: Box::new(iter),
+            line: first_line,
+        }
+    }
+
+    pub(crate) fn error(&self, message: String) -> LoadError {
+        LoadError::Parse {
+            line: self.line,
+            message,
+        }
+    }
+
+    pub(crate) fn next(&mut self, expected: &str) -> Result<&'a str, LoadError> {
+        match self.iter.next() {
+            Some((line, token)) => {
+                self.line = line;
+                Ok(token)
+            }
+            None => Err(self.error(format!("unexpected end of file, expected '{}'", expected))),
+        }
+    }
+
+    pub(crate) fn expect(&mut self, keyword: &str) -> Result<(), LoadError> {
+        let token = self.next(keyword)?;
+        if token != keyword {
+            return Err(self.error(format!("expected '{}', found '{}'", keyword, token)));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn number(&mut self) -> Result<f64, LoadError> {
+        let token = self.next("number")?;
+        token
+            .parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite())
+            .ok_or_else(|| self.error(format!("invalid number '{}'", token)))
+    }
+
+    pub(crate) fn vec3(&mut self) -> Result<Vec3, LoadError> {
+        Ok(Vec3::new(self.number()?, self.number()?, self.number()?))
+    }
+}
+
+fn single(mesh: TriangleMesh) -> HittableList {
+    let mut list = HittableList::new();
+    list.add_obj(mesh);
+    list
+}
+
+// Loads a mesh file, picking the format from its extension. `mat` is used
+// for faces the file does not assign a material to.
+pub fn load_model(path: &Path, mat: MaterialType) -> Result<HittableList, LoadError> {
+    let ext = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase());
+    let result = match ext.as_deref() {
+        Some("obj") => obj::load_obj(path, mat),
+        Some("ply") => ply::load_ply(path, mat).map(single),
+        Some("stl") => stl::load_stl(path, mat).map(single),
+        _ => Err(LoadError::Invalid("unsupported model format".to_string())),
+    };
+    result.map_err(|e| match e {
+        LoadError::File { .. } => e,
+        e => e.in_file(path),
+    })
+}
+
+// Loads an image file such as an environment map or texture as linear
+// values, picking the format from its extension. PPM and PNG hold gamma
+// encoded colours, which are decoded with gamma_to_linear.
+pub fn load_image(path: &Path) -> Result<Image, LoadError> {
+    let (image, encoded) = read_image(path)?;
+    if !encoded {
+        return Ok(image);
+    }
+    let linear = |c: &Color| {
+        Color::new(
+            gamma_to_linear(c.x()),
+            gamma_to_linear(c.y()),
+            gamma_to_linear(c.z()),
+        )
+    };
+    let pixels = image.pixels().iter().map(linear).collect();
+    Ok(Image::from_pixels(image.width(), image.height(), pixels))
+}
+
+// Loads an image with its values as stored, for data such as bump heights
+// that must not be gamma decoded
+pub fn load_image_raw(path: &Path) -> Result<Image, LoadError> {
+    read_image(path).map(|(image, _)| image)
+}
+
+// The image and whether its values are gamma encoded
+fn read_image(path: &Path) -> Result<(Image, bool), LoadError> {
+    let ext = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase());
+    let read = |decode: fn(&[u8]) -> Result<Image, LoadError>| {
+        std::fs::read(path)
+            .map_err(LoadError::from)
+            .and_then(|bytes| decode(&bytes))
+    };
+    let result = match ext.as_deref() {
+        Some("pfm") => read(read_pfm).map(|image| (image, false)),
+        Some("hdr") => read(read_hdr).map(|image| (image, false)),
+        Some("ppm" | "pgm" | "pnm") => read(read_ppm).map(|image| (image, true)),
+        Some("png") => read(read_png).map(|image| (image, true)),
+        _ => Err(LoadError::Invalid("unsupported image format".to_string())),
+    };
+    result.map_err(|e| e.in_file(path))
+}
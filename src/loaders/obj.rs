@@ -0,0 +1,470 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use log::warn;
+
+use crate::{
+    color::Color,
+    material::MaterialType,
+    objects::{
+        hittable_list::HittableList,
+        mesh::{MeshData, TriangleMesh},
+    },
+    vec3::{Point3, Vec3},
+};
+
+use super::LoadError;
+
+fn error(line: usize, message: String) -> LoadError {
+    LoadError::Parse { line, message }
+}
+
+fn parse_f64s<const N: usize>(line: usize, args: &[&str]) -> Result<[f64; N], LoadError> {
+    if args.len() < N {
+        return Err(error(
+            line,
+            format!("expected {} numbers, found {}", N, args.len()),
+        ));
+    }
+    let mut values = [0.0; N];
+    for (value, arg) in values.iter_mut().zip(args) {
+        *value = arg
+            .parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite())
+            .ok_or_else(|| error(line, format!("invalid number '{}'", arg)))?;
+    }
+    Ok(values)
+}
+
+// Wavefront material library. Only the parameters that map onto the
+// existing materials are read:
+//   d < 1 or a refractive illum model -> Dielectric with index Ni
+//   illum 3 or a black Kd with a non-zero Ks -> Metal with albedo Ks,
+//     fuzz falling off as the exponent Ns grows
+//   anything else -> Lambertian with albedo Kd
+pub fn parse_mtl(source: &str) -> Result<HashMap<String, MaterialType>, LoadError> {
+    struct Params {
+        kd: Color,
+        ks: Color,
+        ns: f64,
+        ni: f64,
+        d: f64,
+        illum: i32,
+    }
+
+    fn finish(p: &Params) -> MaterialType {
+        let refractive = matches!(p.illum, 4 | 6 | 7 | 9);
+        let black = |c: &Color| c.x() <= 0.0 && c.y() <= 0.0 && c.z() <= 0.0;
+        if p.d < 1.0 || refractive {
+            MaterialType::dielectric(p.ni)
+        } else if !black(&p.ks) && (p.illum == 3 || black(&p.kd)) {
+            let fuzz = 1.0 - f64::clamp(p.ns / 1000.0, 0.0, 1.0);
+            MaterialType::metal(p.ks, fuzz)
+        } else {
+            MaterialType::lambertian(p.kd)
+        }
+    }
+
+    let mut materials = HashMap::new();
+    let mut current: Option<(String, Params)> = None;
+
+    for (index, raw_line) in source.lines().enumerate() {
+        let line = index + 1;
+        let content = raw_line.split('#').next().unwrap_or("");
+        let tokens: Vec<&str> = content.split_whitespace().collect();
+        let Some((&keyword, args)) = tokens.split_first() else {
+            continue;
+        };
+
+        if keyword == "newmtl" {
+            let name = args.join(" ");
+            if name.is_empty() {
+                return Err(error(line, "newmtl without a name".to_string()));
+            }
+            if let Some((name, params)) = current.take() {
+                materials.insert(name, finish(&params));
+            }
+            let params = Params {
+                kd: Color::new(0.8, 0.8, 0.8),
+                ks: Color::default(),
+                ns: 0.0,
+                ni: 1.5,
+                d: 1.0,
+                illum: 2,
+            };
+            current = Some((name, params));
+            continue;
+        }
+
+        let Some((_, params)) = current.as_mut() else {
+            return Err(error(line, format!("'{}' before any newmtl", keyword)));
+        };
+        match keyword {
+            "Kd" => {
+                let [r, g, b] = parse_f64s(line, args)?;
+                params.kd = Color::new(r, g, b);
+            }
+            "Ks" => {
+                let [r, g, b] = parse_f64s(line, args)?;
+                params.ks = Color::new(r, g, b);
+            }
+            "Ns" => params.ns = parse_f64s::<1>(line, args)?[0],
+            "Ni" => params.ni = parse_f64s::<1>(line, args)?[0],
+            "d" => params.d = parse_f64s::<1>(line, args)?[0],
+            "Tr" => params.d = 1.0 - parse_f64s::<1>(line, args)?[0],
+            "illum" => params.illum = parse_f64s::<1>(line, args)?[0] as i32,
+            // Ambient, emission and texture maps have no equivalent yet
+            _ => {}
+        }
+    }
+
+    if let Some((name, params)) = current {
+        materials.insert(name, finish(&params));
+    }
+    Ok(materials)
+}
+
+// Faces sharing one material, with OBJ's separate position/uv/normal indices
+// flattened into a single vertex index
+#[derive(Default)]
+struct Group {
+    vertices: HashMap<(usize, Option<usize>, Option<usize>), usize>,
+    positions: Vec<Point3>,
+    uvs: Vec<Option<(f64, f64)>>,
+    normals: Vec<Option<Vec3>>,
+    faces: Vec<[usize; 3]>,
+}
+
+impl Group {
+    fn into_mesh_data(self) -> MeshData {
+        // Attributes are only kept when every vertex has them
+        let normals = self.normals.into_iter().collect::<Option<Vec<_>>>();
+        let uvs = self.uvs.into_iter().collect::<Option<Vec<_>>>();
+        MeshData {
+            positions: self.positions,
+            normals,
+            uvs,
+            colors: None,
+            faces: self.faces,
+        }
+    }
+}
+
+// OBJ indices are 1-based, negative values count back from the latest element
+fn resolve(line: usize, token: &str, count: usize, kind: &str) -> Result<usize, LoadError> {
+    let index: i64 = token
+        .parse()
+        .map_err(|_| error(line, format!("invalid {} index '{}'", kind, token)))?;
+    let resolved = if index < 0 {
+        count as i64 + index
+    } else {
+        index - 1
+    };
+    if index == 0 || resolved < 0 || resolved >= count as i64 {
+        return Err(error(
+            line,
+            format!("{} index {} out of range ({} defined)", kind, index, count),
+        ));
+    }
+    Ok(resolved as usize)
+}
+
+// Parses OBJ geometry, triangulating polygons as fans. Faces after `usemtl`
+// take that material from `materials`, the rest use `default_mat`.
+pub fn parse_obj(
+    source: &str,
+    materials: &HashMap<String, MaterialType>,
+    default_mat: MaterialType,
+) -> Result<HittableList, LoadError> {
+    let mut positions: Vec<Point3> = Vec::new();
+    let mut uvs: Vec<(f64, f64)> = Vec::new();
+    let mut normals: Vec<Vec3> = Vec::new();
+
+    // Groups keyed by material name, kept in first-use order
+    let mut groups: Vec<(Option<String>, Group)> = vec![(None, Group::default())];
+    let mut current = 0;
+
+    for (index, raw_line) in source.lines().enumerate() {
+        let line = index + 1;
+        let content = raw_line.split('#').next().unwrap_or("");
+        let tokens: Vec<&str> = content.split_whitespace().collect();
+        let Some((&keyword, args)) = tokens.split_first() else {
+            continue;
+        };
+
+        match keyword {
+            "v" => {
+                let [x, y, z] = parse_f64s(line, args)?;
+                positions.push(Point3::new(x, y, z));
+            }
+            "vt" => {
+                // v defaults to 0, and w of 3D coordinates is unused
+                let [u] = parse_f64s(line, args)?;
+                let v = match args.len() {
+                    1 => 0.0,
+                    _ => parse_f64s::<1>(line, &args[1..])?[0],
+                };
+                uvs.push((u, v));
+            }
+            "vn" => {
+                let [x, y, z] = parse_f64s(line, args)?;
+                normals.push(Vec3::new(x, y, z));
+            }
+            "usemtl" => {
+                let name = args.join(" ");
+                if !materials.contains_key(&name) {
+                    warn!(
+                        "line {}: undefined material '{}', using default",
+                        line, name
+                    );
+                }
+                let key = Some(name);
+                current = match groups.iter().position(|(k, _)| *k == key) {
+                    Some(i) => i,
+                    None => {
+                        groups.push((key, Group::default()));
+                        groups.len() - 1
+                    }
+                };
+            }
+            "f" => {
+                if args.len() < 3 {
+                    return Err(error(line, "face needs at least 3 vertices".to_string()));
+                }
+                let group = &mut groups[current].1;
+                let mut face = Vec::with_capacity(args.len());
+                for arg in args {
+                    let mut parts = arg.split('/');
+                    let p = resolve(line, parts.next().unwrap_or(""), positions.len(), "vertex")?;
+                    let t = match parts.next() {
+                        Some(t) if !t.is_empty() => Some(resolve(line, t, uvs.len(), "uv")?),
+                        _ => None,
+                    };
+                    let n = match parts.next() {
+                        Some(n) if !n.is_empty() => {
+                            Some(resolve(line, n, normals.len(), "normal")?)
+                        }
+                        _ => None,
+                    };
+
+                    let vertex = *group.vertices.entry((p, t, n)).or_insert_with(|| {
+                        group.positions.push(positions[p]);
+                        group.uvs.push(t.map(|t| uvs[t]));
+                        group.normals.push(n.map(|n| normals[n]));
+                        group.positions.len() - 1
+                    });
+                    face.push(vertex);
+                }
+                for k in 1..face.len() - 1 {
+                    group.faces.push([face[0], face[k], face[k + 1]]);
+                }
+            }
+            // Object and group names, smoothing groups and other statements
+            // carry nothing the renderer uses
+            _ => {}
+        }
+    }
+
+    let mut list = HittableList::new();
+    for (name, group) in groups {
+        if group.faces.is_empty() {
+            continue;
+        }
+        let mat = name
+            .and_then(|name| materials.get(&name).cloned())
+            .unwrap_or_else(|| default_mat.clone());
+        let mesh = TriangleMesh::new(group.into_mesh_data(), mat).map_err(LoadError::Invalid)?;
+        list.add_obj(mesh);
+    }
+    Ok(list)
+}
+
+// Loads an OBJ file along with any material libraries it references,
+// resolved relative to the OBJ's directory
+pub fn load_obj(path: &Path, default_mat: MaterialType) -> Result<HittableList, LoadError> {
+    let source = fs::read_to_string(path)?;
+    let dir = path.parent().unwrap_or(Path::new(""));
+
+    let mut materials = HashMap::new();
+    for line in source.lines() {
+        let Some(libs) = line.trim().strip_prefix("mtllib ") else {
+            continue;
+        };
+        for lib in libs.split_whitespace() {
+            let lib_path = dir.join(lib);
+            match fs::read_to_string(&lib_path) {
+                Ok(mtl) => {
+                    materials.extend(parse_mtl(&mtl).map_err(|e| e.in_file(&lib_path))?);
+                }
+                Err(e) => warn!("{}: {}, using default materials", lib_path.display(), e),
+            }
+        }
+    }
+
+    parse_obj(&source, &materials, default_mat)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        hittable::{HitRecord, Hittable},
+        interval::Interval,
+        material::Material,
+        ray::Ray,
+        utils::INFINITY,
+    };
+
+    fn grey() -> MaterialType {
+        MaterialType::lambertian(Color::new(0.5, 0.5, 0.5))
+    }
+
+    fn cast(world: &HittableList, origin: Point3, dir: Vec3) -> HitRecord {
+        let mut rec = HitRecord::default();
+        world.hit(
+            &Ray::new(origin, dir),
+            &Interval::new(0.001, INFINITY),
+            &mut rec,
+        );
+        rec
+    }
+
+    #[test]
+    fn maps_mtl_parameters_to_materials() {
+        let materials = parse_mtl(
+            "newmtl red\n\
+             Kd 0.8 0.1 0.1\n\
+             Ks 0.2 0.2 0.2\n\
+             newmtl chrome\n\
+             Kd 0 0 0\n\
+             Ks 0.9 0.9 0.9\n\
+             Ns 800\n\
+             newmtl glass\n\
+             Kd 1 1 1\n\
+             Ni 1.45\n\
+             d 0.1\n",
+        )
+        .unwrap();
+        assert!(matches!(materials["red"], MaterialType::Lambertian(_)));
+        assert!(matches!(materials["chrome"], MaterialType::Metal(_)));
+        assert!(matches!(materials["glass"], MaterialType::Dielectric(_)));
+    }
+
+    #[test]
+    fn parses_faces_with_indices_and_materials() {
+        let mut materials = HashMap::new();
+        materials.insert("glass".to_string(), MaterialType::dielectric(1.5));
+        let world = parse_obj(
+            "# unit quad as a polygon, then a triangle with negative indices\n\
+             v 0 0 0\n\
+             v 1 0 0\n\
+             v 1 1 0\n\
+             v 0 1 0\n\
+             vt 0 0\n\
+             vn 0 0 1\n\
+             f 1/1/1 2/1/1 3/1/1 4/1/1\n\
+             usemtl glass\n\
+             v 0 0 -2\n\
+             v 1 0 -2\n\
+             v 0 1 -2\n\
+             f -3 -2 -1\n",
+            &materials,
+            grey(),
+        )
+        .unwrap();
+        // One mesh per material
+        assert_eq!(world.len(), 2);
+
+        let rec = cast(
+            &world,
+            Point3::new(0.9, 0.8, 1.0),
+            Vec3::new(0.0, 0.0, -1.0),
+        );
+        assert!(rec.is_hit && rec.front_face);
+        assert_eq!(rec.t, 1.0);
+        assert!(matches!(rec.mat, MaterialType::Lambertian(_)));
+
+        let rec = cast(
+            &world,
+            Point3::new(0.1, 0.1, -1.0),
+            Vec3::new(0.0, 0.0, -1.0),
+        );
+        assert_eq!(rec.t, 1.0);
+        assert!(matches!(rec.mat, MaterialType::Dielectric(_)));
+        assert!(
+            rec.mat
+                .scatter(&Ray::new(rec.p, Vec3::new(0.0, 0.0, -1.0)), &rec)
+                .is_scattered
+        );
+    }
+
+    #[test]
+    fn texture_coordinates_take_one_to_three_values() {
+        let world = parse_obj(
+            "v 0 0 0\n\
+             v 1 0 0\n\
+             v 0 1 0\n\
+             v 5 0 0\n\
+             v 6 0 0\n\
+             v 5 1 0\n\
+             vt 0.25\n\
+             vt 0.5 0.75 1\n\
+             f 1/1 2/1 3/1\n\
+             f 4/2 5/2 6/2\n",
+            &HashMap::new(),
+            grey(),
+        )
+        .unwrap();
+        let down = Vec3::new(0.0, 0.0, -1.0);
+        let rec = cast(&world, Point3::new(0.2, 0.2, 1.0), down);
+        assert!(rec.is_hit);
+        assert!((rec.u - 0.25).abs() < 1e-9 && rec.v.abs() < 1e-9);
+        let rec = cast(&world, Point3::new(5.2, 0.2, 1.0), down);
+        assert!((rec.u - 0.5).abs() < 1e-9 && (rec.v - 0.75).abs() < 1e-9);
+    }
+
+    #[test]
+    fn reports_bad_lines() {
+        let materials = HashMap::new();
+        let err = parse_obj("v 0 0 0\nv 1 0 0\nf 1 2 3\n", &materials, grey())
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 3: vertex index 3 out of range (2 defined)"
+        );
+        let err = parse_obj("v 0 zero 0\n", &materials, grey()).err().unwrap();
+        assert_eq!(err.to_string(), "line 1: invalid number 'zero'");
+        let err = parse_obj("v 0 0 0\nf 1 1\n", &materials, grey())
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "line 2: face needs at least 3 vertices");
+        let err = parse_mtl("Kd 1 1 1\n").err().unwrap();
+        assert_eq!(err.to_string(), "line 1: 'Kd' before any newmtl");
+    }
+
+    #[test]
+    fn loads_material_library_next_to_model() {
+        let dir = std::env::temp_dir().join(format!("obj-loader-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("shiny.mtl"),
+            "newmtl steel\nillum 3\nKs 0.7 0.7 0.7\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("tri.obj"),
+            "mtllib shiny.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl steel\nf 1 2 3\n",
+        )
+        .unwrap();
+
+        let world = load_obj(&dir.join("tri.obj"), grey()).unwrap();
+        let rec = cast(
+            &world,
+            Point3::new(0.2, 0.2, 1.0),
+            Vec3::new(0.0, 0.0, -1.0),
+        );
+        assert!(matches!(rec.mat, MaterialType::Metal(_)));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,124 @@
+use crate::{color::Color, image::Image};
+
+use super::LoadError;
+
+// Splits the first `count` whitespace separated header fields off `bytes`,
+// returning them with the data that follows the single byte after the last.
+// '#' comments between fields are skipped, as netpbm headers allow them.
+pub(crate) fn header_fields(bytes: &[u8], count: usize) -> Option<(Vec<&str>, &[u8])> {
+    let mut fields = Vec::with_capacity(count);
+    let mut pos = 0;
+    while fields.len() < count {
+        loop {
+            match bytes.get(pos)? {
+                b'#' => {
+                    while *bytes.get(pos)? != b'\n' {
+                        pos += 1;
+                    }
+                }
+                b if b.is_ascii_whitespace() => pos += 1,
+                _ => break,
+            }
+        }
+        let start = pos;
+        while !bytes.get(pos)?.is_ascii_whitespace() {
+            pos += 1;
+        }
+        fields.push(std::str::from_utf8(&bytes[start..pos]).ok()?);
+    }
+    Some((fields, &bytes[pos + 1..]))
+}
+
+// Reads colour ("PF") and greyscale ("Pf") maps in either byte order
+pub fn read_pfm(bytes: &[u8]) -> Result<Image, LoadError> {
+    let invalid = |message: &str| LoadError::Invalid(format!("PFM: {}", message));
+    let (fields, data) = header_fields(bytes, 4).ok_or_else(|| invalid("truncated header"))?;
+    let channels = match fields[0] {
+        "PF" => 3,
+        "Pf" => 1,
+        _ => return Err(invalid("missing PF or Pf magic")),
+    };
+    let width: usize = fields[1].parse().map_err(|_| invalid("bad width"))?;
+    let height: usize = fields[2].parse().map_err(|_| invalid("bad height"))?;
+    let scale: f32 = fields[3].parse().map_err(|_| invalid("bad scale"))?;
+    if scale == 0.0 || !scale.is_finite() {
+        return Err(invalid("bad scale"));
+    }
+
+    let needed = width
+        .checked_mul(height)
+        .and_then(|n| n.checked_mul(channels * 4))
+        .ok_or_else(|| invalid("image too large"))?;
+    if data.len() < needed {
+        return Err(invalid(&format!(
+            "truncated data, {} bytes of {}",
+            data.len(),
+            needed
+        )));
+    }
+    let value = |i: usize| {
+        let b = [
+            data[4 * i],
+            data[4 * i + 1],
+            data[4 * i + 2],
+            data[4 * i + 3],
+        ];
+        if scale < 0.0 {
+            f32::from_le_bytes(b) as f64
+        } else {
+            f32::from_be_bytes(b) as f64
+        }
+    };
+
+    let mut image = Image::new(width, height);
+    for y in 0..height {
+        for x in 0..width {
+            // Bottom row first
+            let i = ((height - 1 - y) * width + x) * channels;
+            let color = if channels == 3 {
+                Color::new(value(i), value(i + 1), value(i + 2))
+            } else {
+                Color::new(value(i), value(i), value(i))
+            };
+            image.set(x, y, color);
+        }
+    }
+    Ok(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::pfm::write_pfm;
+
+    #[test]
+    fn reads_written_maps() {
+        let mut image = Image::new(1, 2);
+        image.set(0, 0, Color::new(4.5, 0.0, -1.0));
+        image.set(0, 1, Color::new(0.25, 0.5, 0.75));
+        let mut out = Vec::new();
+        write_pfm(&mut out, &image).unwrap();
+
+        let back = read_pfm(&out).unwrap();
+        assert_eq!(back.get(0, 0).x(), 4.5);
+        assert_eq!(back.get(0, 1).z(), 0.75);
+    }
+
+    #[test]
+    fn reads_big_endian_greyscale() {
+        let mut bytes = b"Pf\n2 1\n1.0\n".to_vec();
+        bytes.extend(1.5f32.to_be_bytes());
+        bytes.extend(0.25f32.to_be_bytes());
+        let image = read_pfm(&bytes).unwrap();
+        assert_eq!((image.width(), image.height()), (2, 1));
+        assert_eq!(image.get(1, 0).y(), 0.25);
+
+        let err = read_pfm(&bytes[..bytes.len() - 1]).err().unwrap();
+        assert_eq!(err.to_string(), "PFM: truncated data, 7 bytes of 8");
+        assert!(read_pfm(b"P6\n1 1\n255\n").is_err());
+        let err = read_pfm(b"PF\n4294967296 4294967296\n-1.0\n")
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "PFM: image too large");
+    }
+}
@@ -0,0 +1,547 @@
+use std::{fs, path::Path};
+
+use crate::{
+    color::{gamma_to_linear, Color},
+    material::MaterialType,
+    objects::mesh::{MeshData, TriangleMesh},
+    vec3::{Point3, Vec3},
+};
+
+use super::{LoadError, Tokens};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Scalar {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl Scalar {
+    fn parse(name: &str) -> Option<Scalar> {
+        match name {
+            "char" | "int8" => Some(Scalar::I8),
+            "uchar" | "uint8" => Some(Scalar::U8),
+            "short" | "int16" => Some(Scalar::I16),
+            "ushort" | "uint16" => Some(Scalar::U16),
+            "int" | "int32" => Some(Scalar::I32),
+            "uint" | "uint32" => Some(Scalar::U32),
+            "float" | "float32" => Some(Scalar::F32),
+            "double" | "float64" => Some(Scalar::F64),
+            _ => None,
+        }
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            Scalar::I8 | Scalar::U8 => 1,
+            Scalar::I16 | Scalar::U16 => 2,
+            Scalar::I32 | Scalar::U32 | Scalar::F32 => 4,
+            Scalar::F64 => 8,
+        }
+    }
+}
+
+enum Property {
+    Scalar(String, Scalar),
+    List(String, Scalar, Scalar),
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+struct Header {
+    format: Format,
+    elements: Vec<Element>,
+    // Line the body starts on, for ASCII error messages
+    body_line: usize,
+}
+
+fn parse_header(text: &str) -> Result<Header, LoadError> {
+    let error = |line: usize, message: String| LoadError::Parse { line, message };
+    let mut lines = text.lines().enumerate().map(|(i, l)| (i + 1, l.trim()));
+
+    match lines.next() {
+        Some((_, "ply")) => {}
+        _ => return Err(error(1, "missing 'ply' magic".to_string())),
+    }
+
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    for (line, content) in lines {
+        let tokens: Vec<&str> = content.split_whitespace().collect();
+        match tokens.as_slice() {
+            ["format", kind, _version] => {
+                format = match *kind {
+                    "ascii" => Some(Format::Ascii),
+                    "binary_little_endian" => Some(Format::BinaryLittleEndian),
+                    _ => return Err(error(line, format!("unsupported format '{}'", kind))),
+                };
+            }
+            ["element", name, count] => {
+                let count = count
+                    .parse()
+                    .map_err(|_| error(line, format!("invalid element count '{}'", count)))?;
+                elements.push(Element {
+                    name: name.to_string(),
+                    count,
+                    properties: Vec::new(),
+                });
+            }
+            ["property", rest @ ..] => {
+                let scalar = |name: &str| {
+                    Scalar::parse(name)
+                        .ok_or_else(|| error(line, format!("unknown property type '{}'", name)))
+                };
+                let property = match rest {
+                    ["list", count_ty, item_ty, name] => {
+                        Property::List(name.to_string(), scalar(count_ty)?, scalar(item_ty)?)
+                    }
+                    [ty, name] => Property::Scalar(name.to_string(), scalar(ty)?),
+                    _ => return Err(error(line, "malformed property".to_string())),
+                };
+                elements
+                    .last_mut()
+                    .ok_or_else(|| error(line, "property before any element".to_string()))?
+                    .properties
+                    .push(property);
+            }
+            ["end_header"] => {
+                let format = format.ok_or_else(|| error(line, "missing format".to_string()))?;
+                return Ok(Header {
+                    format,
+                    elements,
+                    body_line: line + 1,
+                });
+            }
+            ["comment", ..] | ["obj_info", ..] | [] => {}
+            _ => return Err(error(line, format!("unexpected header line '{}'", content))),
+        }
+    }
+    Err(LoadError::Invalid("missing end_header".to_string()))
+}
+
+// Source of property values, either ASCII tokens or little-endian binary
+enum Body<'a> {
+    Ascii(Tokens<'a>),
+    Binary { bytes: &'a [u8], pos: usize },
+}
+
+impl Body<'_> {
+    fn read(&mut self, ty: Scalar, what: &str) -> Result<f64, LoadError> {
+        match self {
+            Body::Ascii(tokens) => {
+                let token = tokens.next(what)?;
+                token
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|v| v.is_finite())
+                    .ok_or_else(|| tokens.error(format!("invalid {} '{}'", what, token)))
+            }
+            Body::Binary { bytes, pos } => {
+                let size = ty.size();
+                let Some(b) = bytes.get(*pos..*pos + size) else {
+                    return Err(LoadError::Invalid(format!(
+                        "truncated PLY: data ends at byte {} while reading {}",
+                        bytes.len(),
+                        what
+                    )));
+                };
+                *pos += size;
+                let value = match ty {
+                    Scalar::I8 => b[0] as i8 as f64,
+                    Scalar::U8 => b[0] as f64,
+                    Scalar::I16 => i16::from_le_bytes([b[0], b[1]]) as f64,
+                    Scalar::U16 => u16::from_le_bytes([b[0], b[1]]) as f64,
+                    Scalar::I32 => i32::from_le_bytes(b.try_into().unwrap()) as f64,
+                    Scalar::U32 => u32::from_le_bytes(b.try_into().unwrap()) as f64,
+                    Scalar::F32 => f32::from_le_bytes(b.try_into().unwrap()) as f64,
+                    Scalar::F64 => f64::from_le_bytes(b.try_into().unwrap()),
+                };
+                Ok(value)
+            }
+        }
+    }
+}
+
+// 8-bit colours are gamma encoded, float colours are taken as linear
+fn color_channel(ty: Scalar, value: f64) -> f64 {
+    match ty {
+        Scalar::F32 | Scalar::F64 => value,
+        _ => gamma_to_linear(value / 255.0),
+    }
+}
+
+fn find(element: &Element, names: &[&str]) -> Option<(usize, Scalar)> {
+    element
+        .properties
+        .iter()
+        .enumerate()
+        .find_map(|(i, p)| match p {
+            Property::Scalar(name, ty) if names.contains(&name.as_str()) => Some((i, *ty)),
+            _ => None,
+        })
+}
+
+fn read_vertices(body: &mut Body, element: &Element, data: &mut MeshData) -> Result<(), LoadError> {
+    let index = |names: &[&str]| find(element, names);
+    let (Some(x), Some(y), Some(z)) = (index(&["x"]), index(&["y"]), index(&["z"])) else {
+        return Err(LoadError::Invalid(
+            "vertex element lacks x, y or z".to_string(),
+        ));
+    };
+    let normal = match (index(&["nx"]), index(&["ny"]), index(&["nz"])) {
+        (Some(nx), Some(ny), Some(nz)) => Some([nx.0, ny.0, nz.0]),
+        _ => None,
+    };
+    let uv = match (
+        index(&["u", "s", "texture_u"]),
+        index(&["v", "t", "texture_v"]),
+    ) {
+        (Some(u), Some(v)) => Some([u.0, v.0]),
+        _ => None,
+    };
+    let color = match (
+        index(&["red", "r"]),
+        index(&["green", "g"]),
+        index(&["blue", "b"]),
+    ) {
+        (Some(r), Some(g), Some(b)) => Some([r, g, b]),
+        _ => None,
+    };
+
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    let mut colors = Vec::new();
+    let mut values = vec![0.0; element.properties.len()];
+    for _ in 0..element.count {
+        for (value, property) in values.iter_mut().zip(&element.properties) {
+            *value = match property {
+                Property::Scalar(_, ty) => body.read(*ty, "vertex property")?,
+                // Lists on vertices are skipped
+                Property::List(_, count_ty, item_ty) => {
+                    let count = body.read(*count_ty, "list length")? as usize;
+                    for _ in 0..count {
+                        body.read(*item_ty, "list item")?;
+                    }
+                    0.0
+                }
+            };
+        }
+
+        data.positions
+            .push(Point3::new(values[x.0], values[y.0], values[z.0]));
+        if let Some([nx, ny, nz]) = normal {
+            normals.push(Vec3::new(values[nx], values[ny], values[nz]));
+        }
+        if let Some([u, v]) = uv {
+            uvs.push((values[u], values[v]));
+        }
+        if let Some([r, g, b]) = color {
+            colors.push(Color::new(
+                color_channel(r.1, values[r.0]),
+                color_channel(g.1, values[g.0]),
+                color_channel(b.1, values[b.0]),
+            ));
+        }
+    }
+    data.normals = normal.map(|_| normals);
+    data.uvs = uv.map(|_| uvs);
+    data.colors = color.map(|_| colors);
+    Ok(())
+}
+
+// List lengths and indices are read as floats, only whole numbers are valid
+fn whole(value: f64) -> Option<usize> {
+    (value >= 0.0 && value.fract() == 0.0).then_some(value as usize)
+}
+
+fn read_faces(
+    body: &mut Body,
+    element: &Element,
+    vertices: usize,
+    data: &mut MeshData,
+) -> Result<(), LoadError> {
+    let indices = element.properties.iter().position(|p| match p {
+        Property::List(name, _, _) => name == "vertex_indices" || name == "vertex_index",
+        _ => false,
+    });
+    let Some(indices) = indices else {
+        return Err(LoadError::Invalid(
+            "face element lacks vertex_indices".to_string(),
+        ));
+    };
+
+    let mut face = Vec::new();
+    for f in 0..element.count {
+        for (i, property) in element.properties.iter().enumerate() {
+            match property {
+                Property::Scalar(_, ty) => {
+                    body.read(*ty, "face property")?;
+                }
+                Property::List(_, count_ty, item_ty) => {
+                    let value = body.read(*count_ty, "list length")?;
+                    let count = whole(value).ok_or_else(|| {
+                        LoadError::Invalid(format!("face {} has a list length of {}", f, value))
+                    })?;
+                    face.clear();
+                    for _ in 0..count {
+                        let value = body.read(*item_ty, "vertex index")?;
+                        if i != indices {
+                            continue;
+                        }
+                        let Some(index) = whole(value).filter(|&index| index < vertices) else {
+                            return Err(LoadError::Invalid(format!(
+                                "face {} references vertex {} of {}",
+                                f, value, vertices
+                            )));
+                        };
+                        face.push(index);
+                    }
+                    if i == indices {
+                        if count < 3 {
+                            return Err(LoadError::Invalid(format!(
+                                "face {} has only {} vertices",
+                                f, count
+                            )));
+                        }
+                        // Triangulate polygons as fans
+                        for k in 1..face.len() - 1 {
+                            data.faces.push([face[0], face[k], face[k + 1]]);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn skip_element(body: &mut Body, element: &Element) -> Result<(), LoadError> {
+    for _ in 0..element.count {
+        for property in &element.properties {
+            match property {
+                Property::Scalar(_, ty) => {
+                    body.read(*ty, "property")?;
+                }
+                Property::List(_, count_ty, item_ty) => {
+                    let count = body.read(*count_ty, "list length")? as usize;
+                    for _ in 0..count {
+                        body.read(*item_ty, "list item")?;
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// Parses ASCII or binary little-endian PLY into a triangle mesh. Vertex
+// colours, when present, replace the albedo of a Lambertian material.
+pub fn parse_ply(bytes: &[u8], mat: MaterialType) -> Result<TriangleMesh, LoadError> {
+    const END: &[u8] = b"end_header";
+    let end = bytes
+        .windows(END.len())
+        .position(|w| w == END)
+        .ok_or_else(|| LoadError::Invalid("missing end_header".to_string()))?;
+    let body_start = bytes[end..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(bytes.len(), |i| end + i + 1);
+    let header_text = std::str::from_utf8(&bytes[..body_start])
+        .map_err(|_| LoadError::Invalid("PLY header is not valid text".to_string()))?;
+    let header = parse_header(header_text)?;
+
+    let body_bytes = &bytes[body_start..];
+    let mut body = match header.format {
+        Format::Ascii => {
+            let text = std::str::from_utf8(body_bytes)
+                .map_err(|_| LoadError::Invalid("ASCII PLY body is not valid text".to_string()))?;
+            Body::Ascii(Tokens::new(text, header.body_line))
+        }
+        Format::BinaryLittleEndian => Body::Binary {
+            bytes: body_bytes,
+            pos: 0,
+        },
+    };
+
+    let vertices = header
+        .elements
+        .iter()
+        .find(|element| element.name == "vertex")
+        .map_or(0, |element| element.count);
+    let mut data = MeshData::default();
+    for element in &header.elements {
+        match element.name.as_str() {
+            "vertex" => read_vertices(&mut body, element, &mut data)?,
+            "face" => read_faces(&mut body, element, vertices, &mut data)?,
+            _ => skip_element(&mut body, element)?,
+        }
+    }
+
+    if data.faces.is_empty() {
+        return Err(LoadError::Invalid("PLY contains no faces".to_string()));
+    }
+    TriangleMesh::new(data, mat).map_err(LoadError::Invalid)
+}
+
+pub fn load_ply(path: &Path, mat: MaterialType) -> Result<TriangleMesh, LoadError> {
+    parse_ply(&fs::read(path)?, mat)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        hittable::{HitRecord, Hittable},
+        interval::Interval,
+        material::Material,
+        ray::Ray,
+        utils::INFINITY,
+    };
+
+    fn white() -> MaterialType {
+        MaterialType::lambertian(Color::new(1.0, 1.0, 1.0))
+    }
+
+    fn cast(mesh: &TriangleMesh, x: f64, y: f64) -> HitRecord {
+        let r = Ray::new(Point3::new(x, y, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let mut rec = HitRecord::default();
+        mesh.hit(&r, &Interval::new(0.001, INFINITY), &mut rec);
+        rec
+    }
+
+    const ASCII: &str = "ply\n\
+        format ascii 1.0\n\
+        comment red, green and blue corners\n\
+        element vertex 3\n\
+        property float x\n\
+        property float y\n\
+        property float z\n\
+        property uchar red\n\
+        property uchar green\n\
+        property uchar blue\n\
+        element face 1\n\
+        property list uchar int vertex_indices\n\
+        end_header\n\
+        0 0 0 255 0 0\n\
+        1 0 0 0 255 0\n\
+        0 1 0 0 0 255\n\
+        3 0 1 2\n";
+
+    #[test]
+    fn interpolates_vertex_colors() {
+        let mesh = parse_ply(ASCII.as_bytes(), white()).unwrap();
+
+        // Close to the red corner
+        let rec = cast(&mesh, 0.01, 0.01);
+        let color = rec.vertex_color.unwrap();
+        assert!(color.x() > 0.9 && color.y() < 0.1 && color.z() < 0.1);
+
+        // Centroid gets an even mix, decoded to linear
+        let rec = cast(&mesh, 1.0 / 3.0, 1.0 / 3.0);
+        let color = rec.vertex_color.unwrap();
+        assert!((color.x() - 1.0 / 3.0).abs() < 1e-9);
+        assert!((color.z() - 1.0 / 3.0).abs() < 1e-9);
+
+        // Lambertian picks the vertex colour up as its attenuation
+        let scatter = rec
+            .mat
+            .scatter(&Ray::new(rec.p, Vec3::new(0.0, 0.0, -1.0)), &rec);
+        assert!((scatter.attenuation.y() - 1.0 / 3.0).abs() < 1e-9);
+    }
+
+    fn binary_quad(truncate: usize) -> Vec<u8> {
+        let mut bytes = b"ply\n\
+            format binary_little_endian 1.0\n\
+            element vertex 4\n\
+            property double x\n\
+            property double y\n\
+            property double z\n\
+            property float red\n\
+            property float green\n\
+            property float blue\n\
+            element face 1\n\
+            property list uchar uint vertex_indices\n\
+            property uchar flags\n\
+            element edge 1\n\
+            property int vertex1\n\
+            property int vertex2\n\
+            end_header\n"
+            .to_vec();
+        for (x, y) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+            for v in [x, y, 0.0f64] {
+                bytes.extend(v.to_le_bytes());
+            }
+            for c in [0.25f32, 0.5, 0.75] {
+                bytes.extend(c.to_le_bytes());
+            }
+        }
+        bytes.push(4);
+        for i in 0..4u32 {
+            bytes.extend(i.to_le_bytes());
+        }
+        bytes.push(7);
+        bytes.extend(0i32.to_le_bytes());
+        bytes.extend(1i32.to_le_bytes());
+        bytes.truncate(bytes.len() - truncate);
+        bytes
+    }
+
+    #[test]
+    fn reads_binary_little_endian() {
+        let mesh = parse_ply(&binary_quad(0), white()).unwrap();
+        assert_eq!(mesh.face_count(), 2);
+        let rec = cast(&mesh, 0.9, 0.8);
+        assert!(rec.is_hit);
+        let color = rec.vertex_color.unwrap();
+        assert!((color.y() - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn reports_errors() {
+        let err = parse_ply(&binary_quad(6), white()).err().unwrap();
+        assert!(err.to_string().starts_with("truncated PLY"));
+
+        let bad = ASCII.replace("1 0 0 0 255 0", "1 0 zero 0 255 0");
+        let err = parse_ply(bad.as_bytes(), white()).err().unwrap();
+        assert_eq!(err.to_string(), "line 15: invalid vertex property 'zero'");
+
+        let bad = ASCII.replace("format ascii", "format binary_big_endian");
+        let err = parse_ply(bad.as_bytes(), white()).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 2: unsupported format 'binary_big_endian'"
+        );
+
+        let bad = ASCII.replace("3 0 1 2", "3 0 1 9");
+        let err = parse_ply(bad.as_bytes(), white()).err().unwrap();
+        assert_eq!(err.to_string(), "face 0 references vertex 9 of 3");
+        for (face, index) in [("3 -1 1 2", "-1"), ("3 0 1.7 2", "1.7")] {
+            let bad = ASCII.replace("3 0 1 2", face);
+            let err = parse_ply(bad.as_bytes(), white()).err().unwrap();
+            let expected = format!("face 0 references vertex {} of 3", index);
+            assert_eq!(err.to_string(), expected);
+        }
+
+        // Faces are counted as written, not as triangulated
+        let bad = ASCII
+            .replace("element face 1", "element face 2")
+            .replace("3 0 1 2\n", "4 0 1 2 1\n2 0 1\n");
+        let err = parse_ply(bad.as_bytes(), white()).err().unwrap();
+        assert_eq!(err.to_string(), "face 1 has only 2 vertices");
+    }
+}
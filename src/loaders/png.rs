@@ -0,0 +1,294 @@
+use crate::{
+    color::Color,
+    image::Image,
+    output::{
+        png::{paeth, SIGNATURE},
+        zlib::{crc32_update, decompress},
+    },
+};
+
+use super::LoadError;
+
+// Inverse of the writer's filter_row, in place given the reconstructed previous scanline
+fn unfilter_row(kind: u8, row: &mut [u8], prev: &[u8], bpp: usize) -> Result<(), LoadError> {
+    for i in 0..row.len() {
+        let a = if i >= bpp { row[i - bpp] } else { 0 };
+        let b = prev[i];
+        let c = if i >= bpp { prev[i - bpp] } else { 0 };
+        let predicted = match kind {
+            0 => 0,
+            1 => a,
+            2 => b,
+            3 => ((a as u16 + b as u16) / 2) as u8,
+            4 => paeth(a, b, c),
+            _ => return Err(invalid(&format!("unknown filter type {}", kind))),
+        };
+        row[i] = row[i].wrapping_add(predicted);
+    }
+    Ok(())
+}
+
+fn invalid(message: &str) -> LoadError {
+    LoadError::Invalid(format!("PNG: {}", message))
+}
+
+struct Header {
+    width: usize,
+    height: usize,
+    depth: u8,
+    color_type: u8,
+}
+
+impl Header {
+    fn channels(&self) -> usize {
+        match self.color_type {
+            0 | 3 => 1,
+            4 => 2,
+            2 => 3,
+            _ => 4,
+        }
+    }
+}
+
+fn parse_header(data: &[u8]) -> Result<Header, LoadError> {
+    if data.len() != 13 {
+        return Err(invalid("bad IHDR length"));
+    }
+    let u32_at = |i: usize| u32::from_be_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]]);
+    let header = Header {
+        width: u32_at(0) as usize,
+        height: u32_at(4) as usize,
+        depth: data[8],
+        color_type: data[9],
+    };
+    let depth_ok = match header.color_type {
+        0 => matches!(header.depth, 1 | 2 | 4 | 8 | 16),
+        3 => matches!(header.depth, 1 | 2 | 4 | 8),
+        2 | 4 | 6 => matches!(header.depth, 8 | 16),
+        _ => {
+            return Err(invalid(&format!(
+                "unknown colour type {}",
+                header.color_type
+            )))
+        }
+    };
+    if !depth_ok {
+        return Err(invalid(&format!(
+            "bit depth {} not allowed for colour type {}",
+            header.depth, header.color_type
+        )));
+    }
+    if data[10] != 0 || data[11] != 0 {
+        return Err(invalid("unknown compression or filter method"));
+    }
+    if data[12] != 0 {
+        return Err(invalid("interlaced images are not supported"));
+    }
+    Ok(header)
+}
+
+// Decodes greyscale, truecolour and palette images of any bit depth, with
+// alpha dropped. Values are returned as stored, scaled to [0, 1] but still
+// gamma encoded.
+pub fn read_png(bytes: &[u8]) -> Result<Image, LoadError> {
+    if bytes.get(..8) != Some(&SIGNATURE[..]) {
+        return Err(invalid("missing signature"));
+    }
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut idat = Vec::new();
+    let mut pos = 8;
+    loop {
+        let chunk = bytes
+            .get(pos..pos + 8)
+            .ok_or_else(|| invalid("truncated chunk"))?;
+        let len = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]) as usize;
+        let kind = &bytes[pos + 4..pos + 8];
+        let body = bytes
+            .get(pos + 8..pos + 12 + len)
+            .ok_or_else(|| invalid("truncated chunk"))?;
+        let (data, crc) = body.split_at(len);
+        if crc32_update(crc32_update(0xffff_ffff, kind), data) ^ 0xffff_ffff
+            != u32::from_be_bytes([crc[0], crc[1], crc[2], crc[3]])
+        {
+            return Err(invalid(&format!(
+                "bad CRC in {} chunk",
+                String::from_utf8_lossy(kind)
+            )));
+        }
+        pos += 12 + len;
+
+        match kind {
+            b"IHDR" => header = Some(parse_header(data)?),
+            b"PLTE" => palette = data,
+            b"IDAT" => idat.extend_from_slice(data),
+            b"IEND" => break,
+            // Critical chunks have an upper case first letter
+            _ if kind[0].is_ascii_uppercase() => {
+                return Err(invalid(&format!(
+                    "unknown critical chunk {}",
+                    String::from_utf8_lossy(kind)
+                )))
+            }
+            _ => {}
+        }
+    }
+
+    let header = header.ok_or_else(|| invalid("missing IHDR chunk"))?;
+    let channels = header.channels();
+    let bits = channels * header.depth as usize;
+    let too_large = || invalid("image too large");
+    let stride = header
+        .width
+        .checked_mul(bits)
+        .ok_or_else(too_large)?
+        .div_ceil(8);
+    let bpp = usize::max(1, bits / 8);
+    let needed = header
+        .height
+        .checked_mul(stride + 1)
+        .ok_or_else(too_large)?;
+    // The image itself holds width * height pixels
+    if header.width.checked_mul(header.height).is_none() {
+        return Err(too_large());
+    }
+
+    let mut raw = decompress(&idat).map_err(invalid)?;
+    if raw.len() < needed {
+        return Err(invalid("not enough image data"));
+    }
+    let mut prev = vec![0u8; stride];
+    let mut image = Image::new(header.width, header.height);
+    for y in 0..header.height {
+        let start = y * (stride + 1);
+        let (kind, row) = (raw[start], &mut raw[start + 1..start + 1 + stride]);
+        unfilter_row(kind, row, &prev, bpp)?;
+
+        let max = ((1u32 << header.depth) - 1) as f64;
+        let sample = |i: usize| -> u32 {
+            match header.depth {
+                8 => row[i] as u32,
+                16 => u16::from_be_bytes([row[2 * i], row[2 * i + 1]]) as u32,
+                d => {
+                    // Packed most significant bits first
+                    let bit = i * d as usize;
+                    (row[bit / 8] >> (8 - d as usize - bit % 8)) as u32 & ((1 << d) - 1)
+                }
+            }
+        };
+        for x in 0..header.width {
+            let s = |c: usize| sample(x * channels + c);
+            let color = match header.color_type {
+                0 | 4 => {
+                    let g = s(0) as f64 / max;
+                    Color::new(g, g, g)
+                }
+                3 => {
+                    let i = 3 * s(0) as usize;
+                    let entry = palette
+                        .get(i..i + 3)
+                        .ok_or_else(|| invalid("palette index out of range"))?;
+                    Color::new(
+                        entry[0] as f64 / 255.0,
+                        entry[1] as f64 / 255.0,
+                        entry[2] as f64 / 255.0,
+                    )
+                }
+                _ => Color::new(s(0) as f64 / max, s(1) as f64 / max, s(2) as f64 / max),
+            };
+            image.set(x, y, color);
+        }
+        prev.copy_from_slice(row);
+    }
+    Ok(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        color::to_rgb_bytes,
+        output::{
+            png::{write_chunk, write_png},
+            zlib::compress,
+        },
+    };
+
+    #[test]
+    fn reads_back_written_png() {
+        let mut image = Image::new(5, 4);
+        for y in 0..4 {
+            for x in 0..5 {
+                image.set(x, y, Color::new(x as f64 / 4.0, y as f64 / 3.0, 0.25));
+            }
+        }
+        let mut out = Vec::new();
+        write_png(&mut out, &image).unwrap();
+        let back = read_png(&out).unwrap();
+        assert_eq!((back.width(), back.height()), (5, 4));
+        for y in 0..4 {
+            for x in 0..5 {
+                let bytes = to_rgb_bytes(&image.get(x, y));
+                let c = back.get(x, y);
+                assert_eq!((c.x() * 255.0).round() as u8, bytes[0]);
+                assert_eq!((c.y() * 255.0).round() as u8, bytes[1]);
+                assert_eq!((c.z() * 255.0).round() as u8, bytes[2]);
+            }
+        }
+
+        let mut corrupt = out.clone();
+        corrupt[20] ^= 1;
+        assert_eq!(
+            read_png(&corrupt).err().unwrap().to_string(),
+            "PNG: bad CRC in IHDR chunk"
+        );
+    }
+
+    fn encode(ihdr: [u8; 13], palette: Option<&[u8]>, raw: &[u8]) -> Vec<u8> {
+        let mut out = SIGNATURE.to_vec();
+        write_chunk(&mut out, b"IHDR", &ihdr).unwrap();
+        if let Some(palette) = palette {
+            write_chunk(&mut out, b"PLTE", palette).unwrap();
+        }
+        write_chunk(&mut out, b"IDAT", &compress(raw)).unwrap();
+        write_chunk(&mut out, b"IEND", &[]).unwrap();
+        out
+    }
+
+    #[test]
+    fn reads_palette_and_sixteen_bit_grey() {
+        // 3x1, 2-bit palette indices 2, 0, 1 packed into one byte
+        let ihdr = [0, 0, 0, 3, 0, 0, 0, 1, 2, 3, 0, 0, 0];
+        let palette = [255, 0, 0, 0, 255, 0, 0, 0, 255];
+        let image = read_png(&encode(ihdr, Some(&palette), &[0, 0b1000_0100])).unwrap();
+        assert_eq!(image.get(0, 0).z(), 1.0);
+        assert_eq!(image.get(1, 0).x(), 1.0);
+        assert_eq!(image.get(2, 0).y(), 1.0);
+
+        // 1x2 16-bit grey with alpha, second row Up-filtered
+        let ihdr = [0, 0, 0, 1, 0, 0, 0, 2, 16, 4, 0, 0, 0];
+        let raw = [0, 0x80, 0x00, 0xff, 0xff, 2, 0x7f, 0xff, 0, 0];
+        let image = read_png(&encode(ihdr, None, &raw)).unwrap();
+        assert_eq!(image.get(0, 0).x(), 32768.0 / 65535.0);
+        assert_eq!(image.get(0, 1).y(), 1.0);
+
+        let ihdr = [0, 0, 0, 1, 0, 0, 0, 1, 8, 2, 0, 0, 1];
+        assert_eq!(
+            read_png(&encode(ihdr, None, &[0, 0, 0, 0]))
+                .err()
+                .unwrap()
+                .to_string(),
+            "PNG: interlaced images are not supported"
+        );
+
+        // 16-bit RGBA at the largest size the header can hold
+        let ihdr = [255, 255, 255, 255, 255, 255, 255, 255, 16, 6, 0, 0, 0];
+        assert_eq!(
+            read_png(&encode(ihdr, None, &[0]))
+                .err()
+                .unwrap()
+                .to_string(),
+            "PNG: image too large"
+        );
+    }
+}
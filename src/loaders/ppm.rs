@@ -0,0 +1,92 @@
+use crate::{color::Color, image::Image};
+
+use super::{pfm::header_fields, LoadError};
+
+// Reads greyscale (P2, P5) and colour (P3, P6) maps with any maximum value.
+// Samples are scaled to [0, 1] but left gamma encoded.
+pub fn read_ppm(bytes: &[u8]) -> Result<Image, LoadError> {
+    let invalid = |message: &str| LoadError::Invalid(format!("PPM: {}", message));
+    let (fields, data) = header_fields(bytes, 4).ok_or_else(|| invalid("truncated header"))?;
+    let (channels, ascii) = match fields[0] {
+        "P2" => (1, true),
+        "P3" => (3, true),
+        "P5" => (1, false),
+        "P6" => (3, false),
+        _ => return Err(invalid("missing P2, P3, P5 or P6 magic")),
+    };
+    let width: usize = fields[1].parse().map_err(|_| invalid("bad width"))?;
+    let height: usize = fields[2].parse().map_err(|_| invalid("bad height"))?;
+    let max: u32 = match fields[3].parse() {
+        Ok(max) if (1..=65535).contains(&max) => max,
+        _ => return Err(invalid("bad maximum value")),
+    };
+
+    let count = width
+        .checked_mul(height)
+        .and_then(|n| n.checked_mul(channels))
+        .ok_or_else(|| invalid("image too large"))?;
+    let samples: Vec<u32> = if ascii {
+        let text = std::str::from_utf8(data).map_err(|_| invalid("non-ASCII data"))?;
+        let samples = text
+            .split_whitespace()
+            .take(count)
+            .map(|s| {
+                s.parse()
+                    .map_err(|_| invalid(&format!("bad sample '{}'", s)))
+            })
+            .collect::<Result<Vec<u32>, _>>()?;
+        if samples.len() < count {
+            return Err(invalid("not enough samples"));
+        }
+        samples
+    } else {
+        // Two big-endian bytes per sample above 255
+        let size = if max > 255 { 2 } else { 1 };
+        let data = count
+            .checked_mul(size)
+            .and_then(|len| data.get(..len))
+            .ok_or_else(|| invalid("not enough samples"))?;
+        data.chunks(size)
+            .map(|b| b.iter().fold(0, |v, &b| v << 8 | b as u32))
+            .collect()
+    };
+
+    let scale = 1.0 / max as f64;
+    let pixels = samples
+        .chunks(channels)
+        .map(|s| {
+            let c = |i: usize| f64::min(s[i] as f64 * scale, 1.0);
+            if channels == 3 {
+                Color::new(c(0), c(1), c(2))
+            } else {
+                Color::new(c(0), c(0), c(0))
+            }
+        })
+        .collect();
+    Ok(Image::from_pixels(width, height, pixels))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_ascii_and_binary_maps() {
+        let image = read_ppm(b"P3\n# made by hand\n2 1 # size\n4\n0 2 4\n4 4 0\n").unwrap();
+        assert_eq!((image.width(), image.height()), (2, 1));
+        assert_eq!(image.get(0, 0).y(), 0.5);
+        assert_eq!(image.get(1, 0).x(), 1.0);
+
+        let mut bytes = b"P5 1 2 1000\n".to_vec();
+        bytes.extend([0x01, 0xf4, 0x03, 0xe8]);
+        let image = read_ppm(&bytes).unwrap();
+        assert_eq!(image.get(0, 0).z(), 0.5);
+        assert_eq!(image.get(0, 1).x(), 1.0);
+
+        let err = read_ppm(&bytes[..bytes.len() - 1]).err().unwrap();
+        assert_eq!(err.to_string(), "PPM: not enough samples");
+        assert!(read_ppm(b"P3 1 1 255 0 0 x").is_err());
+        let err = read_ppm(b"P6 4294967296 4294967296 255\n").err().unwrap();
+        assert_eq!(err.to_string(), "PPM: image too large");
+    }
+}
@@ -0,0 +1,228 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::{
+    material::MaterialType,
+    objects::mesh::{MeshData, TriangleMesh},
+    vec3::{Point3, Vec3},
+};
+
+use super::{LoadError, Tokens};
+
+const HEADER_SIZE: usize = 80;
+const TRIANGLE_SIZE: usize = 50;
+
+struct Facet {
+    normal: Vec3,
+    vertices: [Point3; 3],
+}
+
+// Binary files may also start with "solid", so a matching size or any NUL
+// byte (padding, zero floats, attribute words) marks the file as binary
+fn is_binary(bytes: &[u8]) -> bool {
+    if bytes.len() >= HEADER_SIZE + 4 {
+        let count = u32::from_le_bytes(bytes[80..84].try_into().unwrap()) as usize;
+        if HEADER_SIZE + 4 + count * TRIANGLE_SIZE == bytes.len() {
+            return true;
+        }
+    }
+    !bytes.trim_ascii_start().starts_with(b"solid") || bytes.contains(&0)
+}
+
+fn parse_binary(bytes: &[u8]) -> Result<Vec<Facet>, LoadError> {
+    if bytes.len() < HEADER_SIZE + 4 {
+        return Err(LoadError::Invalid(format!(
+            "truncated binary STL: {} bytes is shorter than the header",
+            bytes.len()
+        )));
+    }
+    let count = u32::from_le_bytes(bytes[80..84].try_into().unwrap()) as usize;
+    let expected = HEADER_SIZE + 4 + count * TRIANGLE_SIZE;
+    if bytes.len() < expected {
+        return Err(LoadError::Invalid(format!(
+            "truncated binary STL: {} triangles need {} bytes, found {}",
+            count,
+            expected,
+            bytes.len()
+        )));
+    }
+
+    let read_vec3 = |offset: usize| {
+        let f = |i: usize| {
+            let start = offset + 4 * i;
+            f32::from_le_bytes(bytes[start..start + 4].try_into().unwrap()) as f64
+        };
+        Vec3::new(f(0), f(1), f(2))
+    };
+    Ok((0..count)
+        .map(|i| {
+            let offset = HEADER_SIZE + 4 + i * TRIANGLE_SIZE;
+            Facet {
+                normal: read_vec3(offset),
+                vertices: [
+                    read_vec3(offset + 12),
+                    read_vec3(offset + 24),
+                    read_vec3(offset + 36),
+                ],
+            }
+        })
+        .collect())
+}
+
+fn parse_ascii(source: &str) -> Result<Vec<Facet>, LoadError> {
+    let mut tokens = Tokens::new(source, 1);
+    let mut facets = Vec::new();
+
+    tokens.expect("solid")?;
+    loop {
+        // The solid name is optional and may be several words long
+        match tokens.next("facet")? {
+            "facet" => {}
+            "endsolid" => break,
+            _ if facets.is_empty() => continue,
+            token => return Err(tokens.error(format!("expected 'facet', found '{}'", token))),
+        }
+        tokens.expect("normal")?;
+        let normal = tokens.vec3()?;
+        tokens.expect("outer")?;
+        tokens.expect("loop")?;
+        let mut vertices = [Point3::default(); 3];
+        for vertex in vertices.iter_mut() {
+            tokens.expect("vertex")?;
+            *vertex = tokens.vec3()?;
+        }
+        tokens.expect("endloop")?;
+        tokens.expect("endfacet")?;
+        facets.push(Facet { normal, vertices });
+    }
+    Ok(facets)
+}
+
+// Builds mesh buffers, merging identical vertices and flipping any facet
+// whose winding disagrees with its stored normal
+fn into_mesh_data(facets: Vec<Facet>) -> MeshData {
+    let mut data = MeshData::default();
+    let mut indices: HashMap<[u64; 3], usize> = HashMap::new();
+
+    for facet in facets {
+        let [a, b, c] = facet.vertices;
+        let winding = Vec3::cross(&(b - a), &(c - a));
+        let vertices = if Vec3::dot(&winding, &facet.normal) < 0.0 {
+            [a, c, b]
+        } else {
+            [a, b, c]
+        };
+
+        let mut face = [0; 3];
+        for (slot, p) in face.iter_mut().zip(vertices) {
+            let key = [p.x().to_bits(), p.y().to_bits(), p.z().to_bits()];
+            *slot = *indices.entry(key).or_insert_with(|| {
+                data.positions.push(p);
+                data.positions.len() - 1
+            });
+        }
+        data.faces.push(face);
+    }
+    data
+}
+
+// Parses binary or ASCII STL data into a flat shaded mesh
+pub fn parse_stl(bytes: &[u8], mat: MaterialType) -> Result<TriangleMesh, LoadError> {
+    let facets = if is_binary(bytes) {
+        parse_binary(bytes)?
+    } else {
+        let source = std::str::from_utf8(bytes)
+            .map_err(|_| LoadError::Invalid("ASCII STL is not valid UTF-8".to_string()))?;
+        parse_ascii(source)?
+    };
+    if facets.is_empty() {
+        return Err(LoadError::Invalid("STL contains no triangles".to_string()));
+    }
+    TriangleMesh::new(into_mesh_data(facets), mat).map_err(LoadError::Invalid)
+}
+
+pub fn load_stl(path: &Path, mat: MaterialType) -> Result<TriangleMesh, LoadError> {
+    parse_stl(&fs::read(path)?, mat)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        color::Color,
+        hittable::{HitRecord, Hittable},
+        interval::Interval,
+        ray::Ray,
+        utils::INFINITY,
+    };
+
+    fn grey() -> MaterialType {
+        MaterialType::lambertian(Color::new(0.5, 0.5, 0.5))
+    }
+
+    // Two triangles making the unit square at z = 0, facing +z. The second
+    // one is stored clockwise and relies on its normal to be flipped.
+    const SQUARE: [[f32; 12]; 2] = [
+        [0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0],
+    ];
+
+    fn binary_square() -> Vec<u8> {
+        let mut bytes = b"solid but actually binary".to_vec();
+        bytes.resize(HEADER_SIZE, 0);
+        bytes.extend((SQUARE.len() as u32).to_le_bytes());
+        for facet in SQUARE {
+            for v in facet {
+                bytes.extend(v.to_le_bytes());
+            }
+            bytes.extend(0u16.to_le_bytes());
+        }
+        bytes
+    }
+
+    fn check_square(mesh: &TriangleMesh) {
+        assert_eq!(mesh.face_count(), 2);
+        for (x, y) in [(0.8, 0.2), (0.2, 0.8)] {
+            let r = Ray::new(Point3::new(x, y, 1.0), Vec3::new(0.0, 0.0, -1.0));
+            let mut rec = HitRecord::default();
+            mesh.hit(&r, &Interval::new(0.001, INFINITY), &mut rec);
+            assert!(rec.is_hit && rec.front_face);
+            assert_eq!(rec.t, 1.0);
+        }
+    }
+
+    #[test]
+    fn reads_binary() {
+        check_square(&parse_stl(&binary_square(), grey()).unwrap());
+    }
+
+    #[test]
+    fn reads_ascii() {
+        let source = "solid square part\n\
+            facet normal 0 0 1\n outer loop\n  vertex 0 0 0\n  vertex 1 0 0\n  vertex 1 1 0\n endloop\nendfacet\n\
+            facet normal 0 0 1\n outer loop\n  vertex 0 0 0\n  vertex 0 1 0\n  vertex 1 1 0\n endloop\nendfacet\n\
+            endsolid square part\n";
+        check_square(&parse_stl(source.as_bytes(), grey()).unwrap());
+    }
+
+    #[test]
+    fn reports_truncated_files() {
+        let bytes = binary_square();
+        let err = parse_stl(&bytes[..bytes.len() - 10], grey()).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "truncated binary STL: 2 triangles need 184 bytes, found 174"
+        );
+        let err = parse_stl(&bytes[..40], grey()).err().unwrap();
+        assert!(err.to_string().contains("shorter than the header"));
+
+        let source = "solid s\nfacet normal 0 0 1\nouter loop\nvertex 0 0 0\nvertex 1 0";
+        let err = parse_stl(source.as_bytes(), grey()).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 5: unexpected end of file, expected 'number'"
+        );
+        let source = "solid s\nfacet normal 0 0 1\nouter loop\nvertex 0 0 x\n";
+        let err = parse_stl(source.as_bytes(), grey()).err().unwrap();
+        assert_eq!(err.to_string(), "line 4: invalid number 'x'");
+    }
+}
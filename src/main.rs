@@ -1,27 +1,46 @@
-mod camera;
-mod color;
-mod hittable;
-mod interval;
-mod material;
-mod objects;
-mod ray;
-mod utils;
-mod vec3;
-
-use camera::Camera;
-use color::Color;
+mod cli;
+
+use cli::Command;
 use env_logger::Builder;
 use log::LevelFilter;
-use material::MaterialType;
-use objects::hittable_list::HittableList;
-use objects::sphere::Sphere;
+use ray_tracer::background::Background;
+use ray_tracer::camera::CameraSettings;
+use ray_tracer::color::Color;
+use ray_tracer::material::MaterialType;
+use ray_tracer::objects::bvh::BvhNode;
+use ray_tracer::objects::hittable_list::HittableList;
+use ray_tracer::objects::sphere::Sphere;
+use ray_tracer::output::write_image;
+use ray_tracer::scene::Scene;
+use ray_tracer::utils::seed_thread_rng;
+use ray_tracer::vec3::Point3;
+use std::env;
 use std::fs::{self, File};
+use std::io::{self, BufWriter};
 use std::path::Path;
-use vec3::{Point3, Vec3};
+use std::process;
+
+const DEBUG_FILE: &str = "debug.log";
 
-const DEBUG_FILE: &'static str = "debug.log";
+fn fail(message: String) -> ! {
+    eprintln!("error: {}", message);
+    process::exit(1);
+}
 
 fn main() {
+    let options = match cli::parse_args(env::args().skip(1)) {
+        Ok(Command::Render(options)) => options,
+        Ok(Command::Help) => {
+            print!("{}", cli::USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, cli::USAGE);
+            process::exit(2);
+        }
+    };
+    let format = options.output_format().unwrap_or_else(|e| fail(e));
+
     let path = Path::new(DEBUG_FILE);
     if path.exists() {
         let _ = fs::remove_file(path);
@@ -32,6 +51,45 @@ fn main() {
         .filter_level(LevelFilter::Debug)
         .init();
 
+    if let Some(seed) = options.seed {
+        seed_thread_rng(seed);
+    }
+
+    let mut scene = match &options.scene {
+        Some(scene_path) => Scene::load(scene_path)
+            .unwrap_or_else(|e| fail(format!("{}: {}", scene_path.display(), e))),
+        None => Scene {
+            camera: CameraSettings::default(),
+            world: default_world(),
+            background: Background::default(),
+        },
+    };
+    options.apply(&mut scene.camera);
+
+    let world = BvhNode::new(scene.world);
+    let mut camera = scene.camera.build();
+    camera.set_background(scene.background);
+    if let Some(threads) = options.threads {
+        camera.set_threads(threads);
+    }
+    if let Some(seed) = options.seed {
+        camera.set_seed(seed);
+    }
+    let image = camera.render(&world);
+
+    let written = match &options.output {
+        Some(output) => File::create(output)
+            .and_then(|file| write_image(&mut BufWriter::new(file), &image, format))
+            .map_err(|e| format!("{}: {}", output.display(), e)),
+        None => write_image(&mut io::stdout().lock(), &image, format)
+            .map_err(|e| format!("stdout: {}", e)),
+    };
+    if let Err(e) = written {
+        fail(e);
+    }
+}
+
+fn default_world() -> HittableList {
     let mut world = HittableList::new();
 
     let material_ground = MaterialType::lambertian(Color::new(0.6, 0.6, 0.0));
@@ -42,85 +100,28 @@ fn main() {
     ));
 
     let material1 = MaterialType::dielectric(1.5);
-    world.add_obj(Sphere::new(
-        Point3::new(0.0, 1.0, 0.0),
-        1.0,
-        material1,
-    ));
+    world.add_obj(Sphere::new(Point3::new(0.0, 1.0, 0.0), 1.0, material1));
 
     let material2 = MaterialType::lambertian(Color::new(0.4, 0.2, 0.1));
-    world.add_obj(Sphere::new(
-        Point3::new(-4.0, 1.0, -1.0),
-        1.0,
-        material2,
-    ));
+    world.add_obj(Sphere::new(Point3::new(-4.0, 1.0, -1.0), 1.0, material2));
 
     let material3 = MaterialType::metal(Color::new(0.7, 0.6, 0.5), 0.3);
-    world.add_obj(Sphere::new(
-        Point3::new(4.0, 1.0, 0.0),
-        1.0,
-        material3,
-    ));
+    world.add_obj(Sphere::new(Point3::new(4.0, 1.0, 0.0), 1.0, material3));
 
     let material4 = MaterialType::dielectric(1.5);
-    world.add_obj(Sphere::new(
-        Point3::new(1.0, 0.0, -1.0),
-        0.6,
-        material4,
-    ));
+    world.add_obj(Sphere::new(Point3::new(1.0, 0.0, -1.0), 0.6, material4));
 
     let material5 = MaterialType::metal(Color::new(0.1, 0.2, 0.9), 0.5);
-    world.add_obj(Sphere::new(
-        Point3::new(-4.0, -1.0, 2.0),
-        0.5,
-        material5,
-    ));
+    world.add_obj(Sphere::new(Point3::new(-4.0, -1.0, 2.0), 0.5, material5));
 
     let material6 = MaterialType::lambertian(Color::random());
-    world.add_obj(Sphere::new(
-        Point3::new(4.0, 4.0, -6.0),
-        0.5,
-        material6,
-    ));
+    world.add_obj(Sphere::new(Point3::new(4.0, 4.0, -6.0), 0.5, material6));
 
     let material7 = MaterialType::lambertian(Color::random());
-    world.add_obj(Sphere::new(
-        Point3::new(1.0, -2.0, -2.0),
-        0.4,
-        material7,
-    ));
+    world.add_obj(Sphere::new(Point3::new(1.0, -2.0, -2.0), 0.4, material7));
 
     let material8 = MaterialType::dielectric(0.5);
-    world.add_obj(Sphere::new(
-        Point3::new(6.0, 7.0, 8.0),
-        0.3,
-        material8,
-    ));
+    world.add_obj(Sphere::new(Point3::new(6.0, 7.0, 8.0), 0.3, material8));
 
-    let aspect_ratio = 16.0 / 9.0;
-    let image_width = 1200;
-    let samples_per_pixel = 500;
-    let recursion_depth = 50;
-
-    let vfov = 20.0;
-    let look_from = Point3::new(1.0, 2.0, 1.0);
-    let look_at = Point3::new(0.0, 0.0, -1.0);
-    let vup = Vec3::new(0.0, 1.0, 0.0);
-
-    let focus_dist = 10.0;
-    let defocus_angle = 0.6;
-
-    let camera = Camera::new(
-        aspect_ratio,
-        image_width,
-        samples_per_pixel,
-        recursion_depth,
-        vfov,
-        look_from,
-        look_at,
-        vup,
-        focus_dist,
-        defocus_angle
-    );
-    camera.render(&world);
+    world
 }
@@ -41,7 +41,7 @@ impl Material for Dielectric {
         };
         Scattered {
             is_scattered: true,
-            ray: Ray::new(rec.p, direction),
+            ray: Ray::with_time(rec.p, direction, r_in.time()),
             attenuation,
         }
     }
@@ -0,0 +1,30 @@
+use crate::{color::Color, hittable::HitRecord, ray::Ray};
+
+use super::{Material, Scattered};
+
+// Glowing surface that emits the same radiance in every direction and
+// reflects nothing
+#[derive(Clone)]
+pub struct DiffuseLight {
+    emit: Color,
+}
+
+impl DiffuseLight {
+    pub fn new(emit: Color) -> Self {
+        DiffuseLight { emit }
+    }
+}
+
+impl Material for DiffuseLight {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Scattered {
+        Scattered {
+            is_scattered: false,
+            attenuation: Color::default(),
+            ray: Ray::with_time(rec.p, *r_in.dir(), r_in.time()),
+        }
+    }
+
+    fn emitted(&self, _rec: &HitRecord) -> Color {
+        self.emit
+    }
+}
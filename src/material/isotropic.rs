@@ -0,0 +1,36 @@
+use crate::{
+    color::Color,
+    hittable::HitRecord,
+    ray::Ray,
+    texture::{Texture, TextureType},
+    vec3::Vec3,
+};
+
+use super::{Material, Scattered};
+
+// Phase function of a participating medium: light leaves a scattering
+// point in any direction with equal probability
+#[derive(Clone)]
+pub struct Isotropic {
+    albedo: TextureType,
+}
+
+impl Isotropic {
+    pub fn new(albedo: Color) -> Self {
+        Isotropic::textured(TextureType::solid(albedo))
+    }
+
+    pub fn textured(albedo: TextureType) -> Self {
+        Isotropic { albedo }
+    }
+}
+
+impl Material for Isotropic {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Scattered {
+        Scattered {
+            is_scattered: true,
+            attenuation: self.albedo.value(rec.u, rec.v, &rec.p),
+            ray: Ray::with_time(rec.p, Vec3::random_unit_vector(), r_in.time()),
+        }
+    }
+}
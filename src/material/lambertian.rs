@@ -1,27 +1,40 @@
 use super::{Material, Scattered};
-use crate::{color::Color, hittable::HitRecord, ray::Ray, vec3::Vec3};
+use crate::{
+    color::Color,
+    hittable::HitRecord,
+    ray::Ray,
+    texture::{Texture, TextureType},
+    vec3::Vec3,
+};
 
 #[derive(Clone)]
 pub struct Lambertian {
-    albedo: Color,
+    albedo: TextureType,
 }
 
 impl Lambertian {
     pub fn new(albedo: Color) -> Self {
+        Lambertian::textured(TextureType::solid(albedo))
+    }
+
+    pub fn textured(albedo: TextureType) -> Self {
         Lambertian { albedo }
     }
 }
 
 impl Material for Lambertian {
-    fn scatter(&self, _r_in: &Ray, rec: &HitRecord) -> super::Scattered {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> super::Scattered {
         let mut scatter_directon = rec.normal + Vec3::random_unit_vector();
         if scatter_directon.near_zero() {
             scatter_directon = rec.normal;
         }
         Scattered {
             is_scattered: true,
-            attenuation: self.albedo.clone(),
-            ray: Ray::new(rec.p, scatter_directon),
+            // Vertex colours take the place of the albedo on meshes that have them
+            attenuation: rec
+                .vertex_color
+                .unwrap_or_else(|| self.albedo.value(rec.u, rec.v, &rec.p)),
+            ray: Ray::with_time(rec.p, scatter_directon, r_in.time()),
         }
     }
 }
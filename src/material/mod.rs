@@ -1,12 +1,18 @@
 use dielectric::Dielectric;
+use diffuse_light::DiffuseLight;
+use isotropic::Isotropic;
 use lambertian::Lambertian;
 use metal::Metal;
+use normal_map::{NormalDetail, NormalMapped};
 
-use crate::{color::Color, hittable::HitRecord, ray::Ray};
+use crate::{color::Color, hittable::HitRecord, ray::Ray, texture::TextureType};
 
 pub mod dielectric;
+pub mod diffuse_light;
+pub mod isotropic;
 pub mod lambertian;
 pub mod metal;
+pub mod normal_map;
 
 pub struct Scattered {
     pub is_scattered: bool,
@@ -14,8 +20,13 @@ pub struct Scattered {
     pub ray: Ray,
 }
 
-pub trait Material {
+pub trait Material: Send + Sync {
     fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Scattered;
+
+    // Light given off at the hit point, black for everything but lights
+    fn emitted(&self, _rec: &HitRecord) -> Color {
+        Color::new(0.0, 0.0, 0.0)
+    }
 }
 
 #[derive(Clone)]
@@ -23,18 +34,39 @@ pub enum MaterialType {
     Lambertian(Lambertian),
     Metal(Metal),
     Dielectric(Dielectric),
+    DiffuseLight(DiffuseLight),
+    Isotropic(Isotropic),
+    NormalMapped(NormalMapped),
 }
 
 impl MaterialType {
     pub fn lambertian(albedo: Color) -> Self {
         MaterialType::Lambertian(Lambertian::new(albedo))
     }
+    pub fn lambertian_texture(albedo: TextureType) -> Self {
+        MaterialType::Lambertian(Lambertian::textured(albedo))
+    }
     pub fn metal(albedo: Color, fuzz: f64) -> Self {
         MaterialType::Metal(Metal::new(albedo, fuzz))
     }
+    pub fn metal_texture(albedo: TextureType, fuzz: f64) -> Self {
+        MaterialType::Metal(Metal::textured(albedo, fuzz))
+    }
     pub fn dielectric(refraction_index: f64) -> Self {
         MaterialType::Dielectric(Dielectric::new(refraction_index))
     }
+    pub fn diffuse_light(emit: Color) -> Self {
+        MaterialType::DiffuseLight(DiffuseLight::new(emit))
+    }
+    pub fn isotropic(albedo: Color) -> Self {
+        MaterialType::Isotropic(Isotropic::new(albedo))
+    }
+    pub fn isotropic_texture(albedo: TextureType) -> Self {
+        MaterialType::Isotropic(Isotropic::textured(albedo))
+    }
+    pub fn normal_mapped(base: MaterialType, detail: NormalDetail) -> Self {
+        MaterialType::NormalMapped(NormalMapped::new(base, detail))
+    }
 }
 
 impl Material for MaterialType {
@@ -43,6 +75,17 @@ impl Material for MaterialType {
             MaterialType::Metal(m) => m.scatter(r_in, rec),
             MaterialType::Lambertian(l) => l.scatter(r_in, rec),
             MaterialType::Dielectric(d) => d.scatter(r_in, rec),
+            MaterialType::DiffuseLight(l) => l.scatter(r_in, rec),
+            MaterialType::Isotropic(i) => i.scatter(r_in, rec),
+            MaterialType::NormalMapped(n) => n.scatter(r_in, rec),
+        }
+    }
+
+    fn emitted(&self, rec: &HitRecord) -> Color {
+        match &self {
+            MaterialType::DiffuseLight(l) => l.emitted(rec),
+            MaterialType::NormalMapped(n) => n.emitted(rec),
+            _ => Color::new(0.0, 0.0, 0.0),
         }
     }
 }
@@ -0,0 +1,206 @@
+use std::sync::Arc;
+
+use crate::{
+    color::Color,
+    hittable::HitRecord,
+    objects::plane::tangent_frame,
+    ray::Ray,
+    texture::{Texture, TextureType},
+    vec3::Vec3,
+};
+
+use super::{Material, MaterialType, Scattered};
+
+// Step in (u, v) used to difference bump heights
+const BUMP_DELTA: f64 = 1e-3;
+
+#[derive(Clone)]
+pub enum NormalDetail {
+    // Tangent space normals stored as colours, x along dpdu, y along dpdv
+    // and z out of the surface. `strength` scales the tilt.
+    Map { texture: TextureType, strength: f64 },
+    // Heights read from the red channel, displacing the surface by `scale`
+    // times the height along its normal
+    Bump { height: TextureType, scale: f64 },
+}
+
+// Wraps another material, replacing the normal it shades with by one
+// perturbed from the surface's tangent frame
+#[derive(Clone)]
+pub struct NormalMapped {
+    base: Arc<MaterialType>,
+    detail: NormalDetail,
+}
+
+impl NormalMapped {
+    pub fn new(base: MaterialType, detail: NormalDetail) -> Self {
+        NormalMapped {
+            base: Arc::new(base),
+            detail,
+        }
+    }
+
+    // The perturbed normal, on the same side of the surface as rec.normal
+    pub fn shading_normal(&self, rec: &HitRecord) -> Vec3 {
+        // Build the frame on the outward side so back faces see the same detail
+        let n = if rec.front_face {
+            rec.normal
+        } else {
+            -rec.normal
+        };
+        let perturbed = match &self.detail {
+            NormalDetail::Map { texture, strength } => {
+                let (t, b) = frame(&n, rec);
+                let c = texture.value(rec.u, rec.v, &rec.p);
+                let (x, y, z) = (2.0 * c.x() - 1.0, 2.0 * c.y() - 1.0, 2.0 * c.z() - 1.0);
+                strength * x * t + strength * y * b + z * n
+            }
+            NormalDetail::Bump { height, scale } => {
+                let h = |du: f64, dv: f64| {
+                    let p = rec.p + du * rec.dpdu + dv * rec.dpdv;
+                    scale * height.value(rec.u + du, rec.v + dv, &p).x()
+                };
+                let h0 = h(0.0, 0.0);
+                let dhdu = (h(BUMP_DELTA, 0.0) - h0) / BUMP_DELTA;
+                let dhdv = (h(0.0, BUMP_DELTA) - h0) / BUMP_DELTA;
+                let (dpdu, dpdv) = if rec.dpdu.near_zero() || rec.dpdv.near_zero() {
+                    frame(&n, rec)
+                } else {
+                    (rec.dpdu, rec.dpdv)
+                };
+                let bumped = Vec3::cross(&(dpdu + dhdu * n), &(dpdv + dhdv * n));
+                // Keep the handedness of the surface normal
+                if Vec3::dot(&bumped, &n) < 0.0 {
+                    -bumped
+                } else {
+                    bumped
+                }
+            }
+        };
+        if perturbed.near_zero() || Vec3::dot(&perturbed, &n) <= 0.0 {
+            return rec.normal;
+        }
+        let perturbed = Vec3::unit_vector(&perturbed);
+        if rec.front_face {
+            perturbed
+        } else {
+            -perturbed
+        }
+    }
+}
+
+// Orthonormal tangent and bitangent around `n`, following dpdu and dpdv
+// where the surface provides them
+fn frame(n: &Vec3, rec: &HitRecord) -> (Vec3, Vec3) {
+    let t = rec.dpdu - Vec3::dot(n, &rec.dpdu) * *n;
+    if t.near_zero() {
+        return tangent_frame(n);
+    }
+    let t = Vec3::unit_vector(&t);
+    let b = Vec3::cross(n, &t);
+    if Vec3::dot(&b, &rec.dpdv) < 0.0 {
+        (t, -b)
+    } else {
+        (t, b)
+    }
+}
+
+impl Material for NormalMapped {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Scattered {
+        let mut shaded = rec.clone();
+        shaded.normal = self.shading_normal(rec);
+        self.base.scatter(r_in, &shaded)
+    }
+
+    fn emitted(&self, rec: &HitRecord) -> Color {
+        self.base.emitted(rec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{texture::image_texture::Filter, vec3::Point3};
+
+    fn flat_record() -> HitRecord {
+        HitRecord {
+            p: Point3::new(0.3, 0.4, 0.0),
+            normal: Vec3::new(0.0, 0.0, 1.0),
+            front_face: true,
+            u: 0.3,
+            v: 0.4,
+            dpdu: Vec3::new(1.0, 0.0, 0.0),
+            dpdv: Vec3::new(0.0, 1.0, 0.0),
+            ..HitRecord::default()
+        }
+    }
+
+    fn grey() -> MaterialType {
+        MaterialType::lambertian(Color::new(0.5, 0.5, 0.5))
+    }
+
+    #[test]
+    fn normal_map_tilts_along_tangents() {
+        // A flat map leaves the normal alone
+        let flat = TextureType::solid(Color::new(0.5, 0.5, 1.0));
+        let mapped = NormalMapped::new(
+            grey(),
+            NormalDetail::Map {
+                texture: flat,
+                strength: 1.0,
+            },
+        );
+        let n = mapped.shading_normal(&flat_record());
+        assert!((n - Vec3::new(0.0, 0.0, 1.0)).near_zero());
+
+        // Tilted towards +u, and back towards the geometric normal at zero strength
+        let tilted = TextureType::solid(Color::new(1.0, 0.5, 1.0));
+        let detail = |strength| NormalDetail::Map {
+            texture: tilted.clone(),
+            strength,
+        };
+        let n = NormalMapped::new(grey(), detail(1.0)).shading_normal(&flat_record());
+        let half = std::f64::consts::FRAC_1_SQRT_2;
+        assert!((n - Vec3::new(half, 0.0, half)).near_zero());
+        let n = NormalMapped::new(grey(), detail(0.0)).shading_normal(&flat_record());
+        assert!((n - Vec3::new(0.0, 0.0, 1.0)).near_zero());
+
+        // Back faces mirror the normal along with the geometry
+        let mut rec = flat_record();
+        rec.normal = -rec.normal;
+        rec.front_face = false;
+        let n = NormalMapped::new(grey(), detail(1.0)).shading_normal(&rec);
+        assert!((n - Vec3::new(-half, 0.0, -half)).near_zero());
+    }
+
+    #[test]
+    fn bump_follows_height_slope() {
+        // Height rising with u, a ramp built from a two texel image
+        let ramp = crate::image::Image::from_pixels(
+            2,
+            1,
+            vec![Color::new(0.0, 0.0, 0.0), Color::new(1.0, 1.0, 1.0)],
+        );
+        let height = TextureType::image(
+            Arc::new(ramp),
+            Filter::Bilinear,
+            crate::texture::image_texture::Wrap::Clamp,
+        );
+        let bumped = NormalMapped::new(grey(), NormalDetail::Bump { height, scale: 0.5 });
+        let n = bumped.shading_normal(&flat_record());
+        // Heights climb 2 per unit u over the middle, times the scale
+        let expected = Vec3::unit_vector(&Vec3::new(-1.0, 0.0, 1.0));
+        assert!((n - expected).near_zero());
+
+        // Constant heights do nothing
+        let flat = NormalMapped::new(
+            grey(),
+            NormalDetail::Bump {
+                height: TextureType::solid(Color::new(0.7, 0.7, 0.7)),
+                scale: 3.0,
+            },
+        );
+        let n = flat.shading_normal(&flat_record());
+        assert!((n - Vec3::new(0.0, 0.0, 1.0)).near_zero());
+    }
+}
@@ -0,0 +1,171 @@
+use std::cmp::Ordering;
+
+use crate::{
+    aabb::{self, Aabb},
+    hittable::{HitRecord, Hittable},
+    interval::Interval,
+    ray::Ray,
+};
+
+use super::hittable_list::HittableList;
+
+pub enum BvhNode {
+    Empty,
+    Leaf(Box<dyn Hittable>),
+    Branch {
+        left: Box<BvhNode>,
+        right: Box<BvhNode>,
+        bbox: Aabb,
+    },
+}
+
+impl BvhNode {
+    pub fn new(list: HittableList) -> Self {
+        let mut objects = list.into_objects();
+        if objects.is_empty() {
+            return BvhNode::Empty;
+        }
+        BvhNode::build(&mut objects)
+    }
+
+    fn build(objects: &mut Vec<Box<dyn Hittable>>) -> Self {
+        if objects.len() == 1 {
+            return BvhNode::Leaf(objects.pop().unwrap());
+        }
+
+        // Split along the longest axis of the span's bounding box
+        let bbox = objects.iter().fold(aabb::EMPTY, |acc, obj| {
+            Aabb::enclose(&acc, &obj.bounding_box())
+        });
+        let axis = bbox.longest_axis();
+        objects.sort_by(|a, b| BvhNode::box_compare(a.as_ref(), b.as_ref(), axis));
+
+        let mut right_objects = objects.split_off(objects.len() / 2);
+        let left = BvhNode::build(objects);
+        let right = BvhNode::build(&mut right_objects);
+
+        BvhNode::Branch {
+            left: Box::new(left),
+            right: Box::new(right),
+            bbox,
+        }
+    }
+
+    fn box_compare(a: &dyn Hittable, b: &dyn Hittable, axis: usize) -> Ordering {
+        let a_axis = a.bounding_box().axis_interval(axis).min;
+        let b_axis = b.bounding_box().axis_interval(axis).min;
+        a_axis.total_cmp(&b_axis)
+    }
+}
+
+impl Hittable for BvhNode {
+    fn hit<'a>(&self, r: &Ray, ray_t: &Interval, rec: &'a mut HitRecord) -> &'a mut HitRecord {
+        match self {
+            BvhNode::Empty => {
+                rec.is_hit = false;
+                rec
+            }
+            BvhNode::Leaf(obj) => obj.hit(r, ray_t, rec),
+            BvhNode::Branch { left, right, bbox } => {
+                rec.is_hit = false;
+                if !bbox.hit(r, ray_t) {
+                    return rec;
+                }
+
+                let hit_left = left.hit(r, ray_t, rec).is_hit;
+                let t_max = if hit_left { rec.t } else { ray_t.max };
+
+                let mut temp_rec = HitRecord::default();
+                let right_rec = right.hit(r, &Interval::new(ray_t.min, t_max), &mut temp_rec);
+                if right_rec.is_hit {
+                    *rec = right_rec.clone();
+                }
+                rec
+            }
+        }
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        match self {
+            BvhNode::Empty => aabb::EMPTY,
+            BvhNode::Leaf(obj) => obj.bounding_box(),
+            BvhNode::Branch { bbox, .. } => *bbox,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        color::Color,
+        material::MaterialType,
+        objects::sphere::Sphere,
+        utils::{random_min_max, INFINITY},
+        vec3::{Point3, Vec3},
+    };
+
+    fn random_spheres(n: usize) -> Vec<(Point3, f64)> {
+        (0..n)
+            .map(|_| (Vec3::random_min_max(-20.0, 20.0), random_min_max(0.2, 2.0)))
+            .collect()
+    }
+
+    fn build_list(spheres: &[(Point3, f64)]) -> HittableList {
+        let mut list = HittableList::new();
+        for (center, radius) in spheres {
+            let mat = MaterialType::lambertian(Color::new(0.5, 0.5, 0.5));
+            list.add_obj(Sphere::new(*center, *radius, mat));
+        }
+        list
+    }
+
+    #[test]
+    fn matches_linear_list() {
+        let spheres = random_spheres(200);
+        let list = build_list(&spheres);
+        let bvh = BvhNode::new(build_list(&spheres));
+        let ray_t = Interval::new(0.001, INFINITY);
+
+        for _ in 0..2000 {
+            let origin = Vec3::random_min_max(-30.0, 30.0);
+            let dir = Vec3::random_min_max(-1.0, 1.0);
+            let r = Ray::new(origin, dir);
+
+            let mut list_rec = HitRecord::default();
+            let mut bvh_rec = HitRecord::default();
+            let list_rec = list.hit(&r, &ray_t, &mut list_rec);
+            let bvh_rec = bvh.hit(&r, &ray_t, &mut bvh_rec);
+
+            assert_eq!(list_rec.is_hit, bvh_rec.is_hit);
+            if list_rec.is_hit {
+                assert_eq!(list_rec.t, bvh_rec.t);
+                assert!((list_rec.p - bvh_rec.p).near_zero());
+            }
+        }
+    }
+
+    #[test]
+    fn single_object() {
+        let bvh = BvhNode::new(build_list(&[(Point3::new(0.0, 0.0, -5.0), 1.0)]));
+        let r = Ray::new(Point3::default(), Vec3::new(0.0, 0.0, -1.0));
+        let mut rec = HitRecord::default();
+        let rec = bvh.hit(&r, &Interval::new(0.001, INFINITY), &mut rec);
+        assert!(rec.is_hit);
+        assert!((rec.t - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn bounding_box_encloses_all() {
+        let spheres = random_spheres(50);
+        let bvh = BvhNode::new(build_list(&spheres));
+        let bbox = bvh.bounding_box();
+        for (center, radius) in spheres {
+            for axis in 0..3 {
+                let ax = bbox.axis_interval(axis);
+                assert!(ax.min <= center[axis] - radius);
+                assert!(ax.max >= center[axis] + radius);
+            }
+        }
+    }
+}
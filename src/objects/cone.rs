@@ -0,0 +1,143 @@
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    interval::Interval,
+    material::MaterialType,
+    polynomial::solve_quadratic,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::cylinder::{azimuth, azimuth_tangent, cap_hit, fill_record, keep_nearest, Nearest};
+
+// Cone with its base disk at `base` and its apex `height` further up the y
+// axis. The base can be left open.
+pub struct Cone {
+    base: Point3,
+    radius: f64,
+    height: f64,
+    capped: bool,
+    mat: MaterialType,
+    bbox: Aabb,
+}
+
+impl Cone {
+    pub fn new(base: Point3, radius: f64, height: f64, capped: bool, mat: MaterialType) -> Self {
+        let radius = f64::max(0.0, radius);
+        let height = f64::max(0.0, height);
+        let bbox = Aabb::from_points(
+            base - Vec3::new(radius, 0.0, radius),
+            base + Vec3::new(radius, height, radius),
+        );
+        Cone {
+            base,
+            radius,
+            height,
+            capped,
+            mat,
+            bbox: bbox.pad_to_minimums(),
+        }
+    }
+}
+
+impl Hittable for Cone {
+    fn hit<'a>(&self, r: &Ray, ray_t: &Interval, rec: &'a mut HitRecord) -> &'a mut HitRecord {
+        let o = *r.origin() - self.base;
+        let d = *r.dir();
+        let mut best = None;
+
+        // x^2 + z^2 = (k (h - y))^2 with k the radius shrink per unit height
+        let k = self.radius / self.height;
+        let k2 = k * k;
+        let above = self.height - o.y();
+        let a = d.x() * d.x() + d.z() * d.z() - k2 * d.y() * d.y();
+        let b = 2.0 * (o.x() * d.x() + o.z() * d.z() + k2 * above * d.y());
+        let c = o.x() * o.x() + o.z() * o.z() - k2 * above * above;
+        // The quadratic also describes the mirrored cone above the apex
+        for t in solve_quadratic(a, b, c) {
+            let p = o + t * d;
+            if ray_t.surrounds(t) && (0.0..=self.height).contains(&p.y()) {
+                let rho = f64::sqrt(p.x() * p.x() + p.z() * p.z());
+                // Going up the side draws in towards the axis
+                let (normal, up) = if rho > 0.0 {
+                    (
+                        Vec3::unit_vector(&Vec3::new(p.x() / rho, k, p.z() / rho)),
+                        Vec3::new(-k * p.x() / rho, 1.0, -k * p.z() / rho),
+                    )
+                } else {
+                    (Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 1.0, 0.0))
+                };
+                let uv = (azimuth(p.x(), p.z()), p.y() / self.height);
+                let tangents = (azimuth_tangent(p.x(), p.z()), self.height * up);
+                keep_nearest(
+                    &mut best,
+                    Nearest {
+                        t,
+                        normal,
+                        uv,
+                        tangents,
+                    },
+                );
+                break;
+            }
+        }
+
+        if self.capped {
+            if let Some(hit) = cap_hit(&o, &d, ray_t, 0.0, self.radius, -1.0) {
+                keep_nearest(&mut best, hit);
+            }
+        }
+
+        fill_record(r, best, &self.mat, rec)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{color::Color, utils::INFINITY};
+
+    fn cast(obj: &Cone, origin: Point3, dir: Vec3) -> HitRecord {
+        let mut rec = HitRecord::default();
+        obj.hit(
+            &Ray::new(origin, dir),
+            &Interval::new(0.001, INFINITY),
+            &mut rec,
+        );
+        rec
+    }
+
+    #[test]
+    fn slanted_side_and_base() {
+        // 45 degree cone: radius 1 at y = 0, apex at y = 1
+        let cone = Cone::new(
+            Point3::new(0.0, 0.0, 0.0),
+            1.0,
+            1.0,
+            true,
+            MaterialType::lambertian(Color::new(0.5, 0.5, 0.5)),
+        );
+
+        let rec = cast(&cone, Point3::new(3.0, 0.5, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+        assert!(rec.is_hit && rec.front_face);
+        assert!((rec.t - 2.5).abs() < 1e-12);
+        let n = std::f64::consts::FRAC_1_SQRT_2;
+        assert!((rec.normal.x() - n).abs() < 1e-12 && (rec.normal.y() - n).abs() < 1e-12);
+        // Up the side towards the apex, and around it
+        assert!((rec.dpdv - Vec3::new(-1.0, 1.0, 0.0)).near_zero());
+        assert!(Vec3::dot(&rec.dpdu, &rec.normal).abs() < 1e-12 && rec.dpdu.z() < 0.0);
+
+        let rec = cast(&cone, Point3::new(0.5, -2.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        assert!(rec.is_hit && rec.front_face);
+        assert!((rec.t - 2.0).abs() < 1e-12);
+        assert_eq!(rec.normal.y(), -1.0);
+
+        // Misses beside the apex and the mirrored nappe above it
+        assert!(!cast(&cone, Point3::new(3.0, 0.9, 0.5), Vec3::new(-1.0, 0.0, 0.0)).is_hit);
+        assert!(!cast(&cone, Point3::new(3.0, 1.5, 0.0), Vec3::new(-1.0, 0.0, 0.0)).is_hit);
+    }
+}
@@ -0,0 +1,142 @@
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    interval::{self, Interval},
+    material::MaterialType,
+    ray::Ray,
+    utils::{random_f64, INFINITY},
+    vec3::Vec3,
+};
+
+// Fog or smoke of uniform density filling a closed boundary. Rays travel an
+// exponentially distributed distance inside before scattering off the phase
+// material, usually isotropic, or pass straight through. The boundary is
+// assumed convex: only the span from the first entry to the next exit is
+// filled.
+pub struct ConstantMedium<H: Hittable> {
+    boundary: H,
+    neg_inv_density: f64,
+    phase: MaterialType,
+}
+
+impl<H: Hittable> ConstantMedium<H> {
+    pub fn new(boundary: H, density: f64, phase: MaterialType) -> Self {
+        ConstantMedium {
+            boundary,
+            neg_inv_density: -1.0 / density,
+            phase,
+        }
+    }
+}
+
+impl<H: Hittable> Hittable for ConstantMedium<H> {
+    fn hit<'a>(&self, r: &Ray, ray_t: &Interval, rec: &'a mut HitRecord) -> &'a mut HitRecord {
+        rec.is_hit = false;
+        // Entry and exit along the whole line, so rays starting inside the
+        // medium still find where they came in
+        let mut entry = HitRecord::default();
+        if !self.boundary.hit(r, &interval::UNIVERSE, &mut entry).is_hit {
+            return rec;
+        }
+        let mut exit = HitRecord::default();
+        let beyond = Interval::new(entry.t + 0.0001, INFINITY);
+        if !self.boundary.hit(r, &beyond, &mut exit).is_hit {
+            return rec;
+        }
+
+        let t_min = f64::max(entry.t, f64::max(ray_t.min, 0.0));
+        let t_max = f64::min(exit.t, ray_t.max);
+        if t_min >= t_max {
+            return rec;
+        }
+        let ray_length = r.dir().length();
+        let distance_inside = (t_max - t_min) * ray_length;
+        let hit_distance = self.neg_inv_density * random_f64().ln();
+        if hit_distance > distance_inside {
+            return rec;
+        }
+
+        rec.t = t_min + hit_distance / ray_length;
+        rec.p = r.at(rec.t);
+        // A volume has no surface, these are arbitrary but well formed
+        rec.normal = Vec3::new(1.0, 0.0, 0.0);
+        rec.front_face = true;
+        (rec.u, rec.v) = (0.0, 0.0);
+        rec.dpdu = Vec3::new(0.0, 1.0, 0.0);
+        rec.dpdv = Vec3::new(0.0, 0.0, 1.0);
+        rec.vertex_color = None;
+        rec.mat = self.phase.clone();
+        rec.is_hit = true;
+        rec
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.boundary.bounding_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        color::Color, material::Material, objects::sphere::Sphere, utils::seed_thread_rng,
+        vec3::Point3,
+    };
+
+    fn fog(density: f64) -> ConstantMedium<Sphere> {
+        let glass = MaterialType::dielectric(1.5);
+        let smoke = MaterialType::isotropic(Color::new(0.5, 0.5, 0.5));
+        ConstantMedium::new(Sphere::new(Point3::default(), 1.0, glass), density, smoke)
+    }
+
+    fn cast(medium: &ConstantMedium<Sphere>, origin: Point3) -> HitRecord {
+        let r = Ray::new(origin, Vec3::new(0.0, 0.0, -2.0));
+        let mut rec = HitRecord::default();
+        medium.hit(&r, &Interval::new(0.001, INFINITY), &mut rec);
+        rec
+    }
+
+    #[test]
+    fn transmission_follows_density() {
+        // Through 2 units of density 0.5, e^-1 of the rays get across
+        seed_thread_rng(25);
+        let medium = fog(0.5);
+        let trials = 20000;
+        let mut passed = 0;
+        for _ in 0..trials {
+            let rec = cast(&medium, Point3::new(0.0, 0.0, 5.0));
+            if !rec.is_hit {
+                passed += 1;
+            } else {
+                // Scatter points lie inside the boundary, t in ray units
+                assert!(rec.p.length() <= 1.0 + 1e-9);
+                assert!((rec.t - (5.0 - rec.p.z()) / 2.0).abs() < 1e-9);
+                assert!(matches!(rec.mat, MaterialType::Isotropic(_)));
+            }
+        }
+        let fraction = passed as f64 / trials as f64;
+        assert!((fraction - (-1.0f64).exp()).abs() < 0.02);
+
+        // Rays that miss the boundary never scatter
+        assert!(!cast(&fog(1e6), Point3::new(2.0, 0.0, 5.0)).is_hit);
+    }
+
+    #[test]
+    fn dense_media_scatter_at_the_edge() {
+        let medium = fog(1e6);
+        let rec = cast(&medium, Point3::new(0.0, 0.0, 5.0));
+        assert!(rec.is_hit && (rec.p.z() - 1.0).abs() < 1e-3);
+
+        // Starting inside, the ray scatters right away rather than at the entry
+        let rec = cast(&medium, Point3::new(0.0, 0.0, 0.5));
+        assert!(rec.is_hit && (rec.p.z() - 0.5).abs() < 1e-2);
+        assert!(!cast(&medium, Point3::new(0.0, 0.0, -1.5)).is_hit);
+
+        let scattered = rec
+            .mat
+            .scatter(&Ray::new(rec.p, Vec3::new(0.0, 0.0, -1.0)), &rec);
+        assert!(scattered.is_scattered);
+        assert!((scattered.ray.dir().length() - 1.0).abs() < 1e-9);
+        assert_eq!(scattered.attenuation.x(), 0.5);
+    }
+}
@@ -0,0 +1,83 @@
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    interval::Interval,
+    material::MaterialType,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{hittable_list::HittableList, quad::Quad};
+
+// Axis-aligned box between two opposite corners, built from six outward
+// facing quads. Each face carries its own (u, v) over the unit square.
+pub struct Cuboid {
+    sides: HittableList,
+}
+
+impl Cuboid {
+    pub fn new(a: Point3, b: Point3, mat: MaterialType) -> Self {
+        let min = Point3::new(a.x().min(b.x()), a.y().min(b.y()), a.z().min(b.z()));
+        let max = Point3::new(a.x().max(b.x()), a.y().max(b.y()), a.z().max(b.z()));
+        let dx = Vec3::new(max.x() - min.x(), 0.0, 0.0);
+        let dy = Vec3::new(0.0, max.y() - min.y(), 0.0);
+        let dz = Vec3::new(0.0, 0.0, max.z() - min.z());
+
+        let mut sides = HittableList::new();
+        let mut side = |q: Point3, u: Vec3, v: Vec3| sides.add_obj(Quad::new(q, u, v, mat.clone()));
+        side(Point3::new(min.x(), min.y(), max.z()), dx, dy); // front
+        side(Point3::new(max.x(), min.y(), max.z()), -dz, dy); // right
+        side(Point3::new(max.x(), min.y(), min.z()), -dx, dy); // back
+        side(Point3::new(min.x(), min.y(), min.z()), dz, dy); // left
+        side(Point3::new(min.x(), max.y(), max.z()), dx, -dz); // top
+        side(Point3::new(min.x(), min.y(), min.z()), dx, dz); // bottom
+
+        Cuboid { sides }
+    }
+}
+
+impl Hittable for Cuboid {
+    fn hit<'a>(&self, r: &Ray, ray_t: &Interval, rec: &'a mut HitRecord) -> &'a mut HitRecord {
+        self.sides.hit(r, ray_t, rec)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.sides.bounding_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{color::Color, utils::INFINITY};
+
+    #[test]
+    fn faces_point_outwards() {
+        let cuboid = Cuboid::new(
+            Point3::new(1.0, 2.0, 3.0),
+            Point3::new(-1.0, -2.0, -3.0),
+            MaterialType::lambertian(Color::new(0.5, 0.5, 0.5)),
+        );
+        let axes = [
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        ];
+        for axis in axes {
+            for sign in [1.0, -1.0] {
+                // From outside every face is a front face with an outward normal
+                let r = Ray::new(10.0 * sign * axis, -sign * axis);
+                let mut rec = HitRecord::default();
+                cuboid.hit(&r, &Interval::new(0.001, INFINITY), &mut rec);
+                assert!(rec.is_hit && rec.front_face);
+                assert!((rec.normal - sign * axis).near_zero());
+                assert!((rec.u - 0.5).abs() < 1e-12 && (rec.v - 0.5).abs() < 1e-12);
+
+                // From inside the ray meets the back of the face ahead
+                let r = Ray::new(Point3::default(), sign * axis);
+                cuboid.hit(&r, &Interval::new(0.001, INFINITY), &mut rec);
+                assert!(rec.is_hit && !rec.front_face);
+            }
+        }
+    }
+}
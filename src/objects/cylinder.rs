@@ -0,0 +1,237 @@
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    interval::Interval,
+    material::MaterialType,
+    polynomial::solve_quadratic,
+    ray::Ray,
+    utils::PI,
+    vec3::{Point3, Vec3},
+};
+
+// Closest of several candidate surface hits: t, outward normal, (u, v) and
+// the derivatives of the point along u and v
+pub(crate) struct Nearest {
+    pub t: f64,
+    pub normal: Vec3,
+    pub uv: (f64, f64),
+    pub tangents: (Vec3, Vec3),
+}
+
+pub(crate) fn keep_nearest(best: &mut Option<Nearest>, hit: Nearest) {
+    if best.as_ref().is_none_or(|b| hit.t < b.t) {
+        *best = Some(hit);
+    }
+}
+
+// Writes the nearest candidate into the record, or marks a miss
+pub(crate) fn fill_record<'a>(
+    r: &Ray,
+    best: Option<Nearest>,
+    mat: &MaterialType,
+    rec: &'a mut HitRecord,
+) -> &'a mut HitRecord {
+    let Some(hit) = best else {
+        rec.is_hit = false;
+        return rec;
+    };
+    rec.t = hit.t;
+    rec.p = r.at(hit.t);
+    rec.self_face_normal(r, hit.normal);
+    (rec.u, rec.v) = hit.uv;
+    (rec.dpdu, rec.dpdv) = hit.tangents;
+    rec.is_hit = true;
+    rec.mat = mat.clone();
+    rec.vertex_color = None;
+    rec
+}
+
+// Angle around the y axis as a texture coordinate, matching the sphere
+pub(crate) fn azimuth(x: f64, z: f64) -> f64 {
+    (f64::atan2(-z, x) + PI) / (2.0 * PI)
+}
+
+// Derivative of a point at (x, z) along the azimuth coordinate
+pub(crate) fn azimuth_tangent(x: f64, z: f64) -> Vec3 {
+    2.0 * PI * Vec3::new(z, 0.0, -x)
+}
+
+// Hit on the disk of `radius` around the y axis at local height `y`
+pub(crate) fn cap_hit(
+    o: &Vec3,
+    d: &Vec3,
+    ray_t: &Interval,
+    y: f64,
+    radius: f64,
+    normal_y: f64,
+) -> Option<Nearest> {
+    if d.y() == 0.0 {
+        return None;
+    }
+    let t = (y - o.y()) / d.y();
+    let (x, z) = (o.x() + t * d.x(), o.z() + t * d.z());
+    if !ray_t.surrounds(t) || x * x + z * z > radius * radius {
+        return None;
+    }
+    Some(Nearest {
+        t,
+        normal: Vec3::new(0.0, normal_y, 0.0),
+        uv: (0.5 + x / (2.0 * radius), 0.5 + z / (2.0 * radius)),
+        tangents: (
+            Vec3::new(2.0 * radius, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 2.0 * radius),
+        ),
+    })
+}
+
+// Cylinder standing on the disk at `base`, running up the y axis. Without
+// caps it is an open tube whose inside shows back faces.
+pub struct Cylinder {
+    base: Point3,
+    radius: f64,
+    height: f64,
+    capped: bool,
+    mat: MaterialType,
+    bbox: Aabb,
+}
+
+impl Cylinder {
+    pub fn new(base: Point3, radius: f64, height: f64, capped: bool, mat: MaterialType) -> Self {
+        let radius = f64::max(0.0, radius);
+        let height = f64::max(0.0, height);
+        let bbox = Aabb::from_points(
+            base - Vec3::new(radius, 0.0, radius),
+            base + Vec3::new(radius, height, radius),
+        );
+        Cylinder {
+            base,
+            radius,
+            height,
+            capped,
+            mat,
+            bbox: bbox.pad_to_minimums(),
+        }
+    }
+}
+
+impl Hittable for Cylinder {
+    fn hit<'a>(&self, r: &Ray, ray_t: &Interval, rec: &'a mut HitRecord) -> &'a mut HitRecord {
+        let o = *r.origin() - self.base;
+        let d = *r.dir();
+        let mut best = None;
+
+        let a = d.x() * d.x() + d.z() * d.z();
+        let b = 2.0 * (o.x() * d.x() + o.z() * d.z());
+        let c = o.x() * o.x() + o.z() * o.z() - self.radius * self.radius;
+        // Rays parallel to the axis only meet the caps
+        if a > 0.0 {
+            for t in solve_quadratic(a, b, c) {
+                let p = o + t * d;
+                if ray_t.surrounds(t) && (0.0..=self.height).contains(&p.y()) {
+                    let normal = Vec3::new(p.x(), 0.0, p.z()) / self.radius;
+                    let uv = (azimuth(p.x(), p.z()), p.y() / self.height);
+                    let tangents = (
+                        azimuth_tangent(p.x(), p.z()),
+                        Vec3::new(0.0, self.height, 0.0),
+                    );
+                    keep_nearest(
+                        &mut best,
+                        Nearest {
+                            t,
+                            normal,
+                            uv,
+                            tangents,
+                        },
+                    );
+                    break;
+                }
+            }
+        }
+
+        if self.capped {
+            for (y, normal_y) in [(0.0, -1.0), (self.height, 1.0)] {
+                if let Some(hit) = cap_hit(&o, &d, ray_t, y, self.radius, normal_y) {
+                    keep_nearest(&mut best, hit);
+                }
+            }
+        }
+
+        fill_record(r, best, &self.mat, rec)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{color::Color, utils::INFINITY};
+
+    fn cylinder(capped: bool) -> Cylinder {
+        Cylinder::new(
+            Point3::new(0.0, -1.0, 0.0),
+            1.0,
+            2.0,
+            capped,
+            MaterialType::lambertian(Color::new(0.5, 0.5, 0.5)),
+        )
+    }
+
+    fn cast(obj: &Cylinder, origin: Point3, dir: Vec3) -> HitRecord {
+        let mut rec = HitRecord::default();
+        obj.hit(
+            &Ray::new(origin, dir),
+            &Interval::new(0.001, INFINITY),
+            &mut rec,
+        );
+        rec
+    }
+
+    #[test]
+    fn side_and_caps() {
+        let capped = cylinder(true);
+        let rec = cast(
+            &capped,
+            Point3::new(0.0, 0.5, 5.0),
+            Vec3::new(0.0, 0.0, -1.0),
+        );
+        assert!(rec.is_hit && rec.front_face);
+        assert!((rec.t - 4.0).abs() < 1e-12);
+        assert!((rec.normal.z() - 1.0).abs() < 1e-12);
+        assert!((rec.v - 0.75).abs() < 1e-12);
+
+        let rec = cast(
+            &capped,
+            Point3::new(0.5, 5.0, 0.0),
+            Vec3::new(0.0, -1.0, 0.0),
+        );
+        assert!(rec.is_hit && rec.front_face);
+        assert!((rec.t - 4.0).abs() < 1e-12);
+        assert_eq!(rec.normal.y(), 1.0);
+
+        // Past the top edge
+        assert!(
+            !cast(
+                &capped,
+                Point3::new(0.0, 1.5, 5.0),
+                Vec3::new(0.0, 0.0, -1.0)
+            )
+            .is_hit
+        );
+    }
+
+    #[test]
+    fn open_tube_shows_its_inside() {
+        let open = cylinder(false);
+        // Down the axis there is nothing to hit
+        assert!(!cast(&open, Point3::new(0.5, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0)).is_hit);
+
+        // Slanting in through the open top meets the far wall from inside
+        let rec = cast(&open, Point3::new(0.0, 1.5, 0.0), Vec3::new(1.0, -1.0, 0.0));
+        assert!(rec.is_hit && !rec.front_face);
+        assert!((rec.t - 1.0).abs() < 1e-12);
+        assert!((rec.normal.x() + 1.0).abs() < 1e-12);
+    }
+}
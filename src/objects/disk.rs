@@ -0,0 +1,106 @@
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    interval::Interval,
+    material::MaterialType,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::plane::{intersect_plane, tangent_frame};
+
+// Flat disk facing `normal`. UVs map the disk into the unit square, with the
+// center at (0.5, 0.5).
+pub struct Disk {
+    center: Point3,
+    normal: Vec3,
+    radius: f64,
+    tangent: Vec3,
+    bitangent: Vec3,
+    mat: MaterialType,
+    bbox: Aabb,
+}
+
+impl Disk {
+    pub fn new(center: Point3, normal: Vec3, radius: f64, mat: MaterialType) -> Self {
+        let normal = Vec3::unit_vector(&normal);
+        let radius = f64::max(0.0, radius);
+        let (tangent, bitangent) = tangent_frame(&normal);
+        // Along each axis the rim reaches radius * sin(angle to the normal)
+        let extent = |n: f64| radius * f64::sqrt(f64::max(0.0, 1.0 - n * n));
+        let half = Vec3::new(extent(normal.x()), extent(normal.y()), extent(normal.z()));
+        Disk {
+            center,
+            normal,
+            radius,
+            tangent,
+            bitangent,
+            mat,
+            bbox: Aabb::from_points(center - half, center + half).pad_to_minimums(),
+        }
+    }
+}
+
+impl Hittable for Disk {
+    fn hit<'a>(&self, r: &Ray, ray_t: &Interval, rec: &'a mut HitRecord) -> &'a mut HitRecord {
+        let Some(t) = intersect_plane(r, ray_t, &self.center, &self.normal) else {
+            rec.is_hit = false;
+            return rec;
+        };
+        let p = r.at(t);
+        let offset = p - self.center;
+        if offset.length_squared() > self.radius * self.radius {
+            rec.is_hit = false;
+            return rec;
+        }
+
+        rec.t = t;
+        rec.p = p;
+        rec.self_face_normal(r, self.normal);
+        let scale = 0.5 / self.radius;
+        rec.u = 0.5 + Vec3::dot(&offset, &self.tangent) * scale;
+        rec.v = 0.5 + Vec3::dot(&offset, &self.bitangent) * scale;
+        (rec.dpdu, rec.dpdv) = (self.tangent / scale, self.bitangent / scale);
+        rec.is_hit = true;
+        rec.mat = self.mat.clone();
+        rec.vertex_color = None;
+        rec
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{color::Color, utils::INFINITY};
+
+    #[test]
+    fn hits_within_radius() {
+        let disk = Disk::new(
+            Point3::new(0.0, 0.0, -2.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            1.0,
+            MaterialType::lambertian(Color::new(0.5, 0.5, 0.5)),
+        );
+        let cast = |x: f64, y: f64| {
+            let r = Ray::new(Point3::new(x, y, 0.0), Vec3::new(0.0, 0.0, -1.0));
+            let mut rec = HitRecord::default();
+            disk.hit(&r, &Interval::new(0.001, INFINITY), &mut rec);
+            rec
+        };
+
+        let rec = cast(0.5, 0.0);
+        assert!(rec.is_hit && rec.front_face);
+        assert!((rec.t - 2.0).abs() < 1e-12);
+        assert!((rec.u - 0.75).abs() < 1e-12);
+        assert!((rec.v - 0.5).abs() < 1e-12);
+        assert!(!cast(0.8, 0.8).is_hit);
+
+        let bbox = disk.bounding_box();
+        assert_eq!(bbox.x.max, 1.0);
+        assert!(bbox.z.size() > 0.0 && bbox.z.size() < 0.01);
+    }
+}
@@ -1,29 +1,56 @@
 use crate::{
+    aabb::Aabb,
     hittable::{HitRecord, Hittable},
     interval::Interval,
 };
 
-pub struct HittableList<T: Hittable> {
-    objects: Vec<Box<T>>,
+pub struct HittableList {
+    objects: Vec<Box<dyn Hittable>>,
+    bbox: Aabb,
 }
 
-impl<T: Hittable> HittableList<T> {
+impl HittableList {
     pub fn new() -> Self {
         HittableList {
             objects: Vec::new(),
+            bbox: Aabb::default(),
         }
     }
 
-    pub fn add_obj(&mut self, obj: T) {
-        self.objects.push(Box::new(obj));
+    pub fn add_obj<T: Hittable + 'static>(&mut self, obj: T) {
+        self.add_boxed(Box::new(obj));
+    }
+
+    pub fn add_boxed(&mut self, obj: Box<dyn Hittable>) {
+        self.bbox = Aabb::enclose(&self.bbox, &obj.bounding_box());
+        self.objects.push(obj);
     }
 
     pub fn clear(&mut self) {
         self.objects.clear();
+        self.bbox = Aabb::default();
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    pub fn into_objects(self) -> Vec<Box<dyn Hittable>> {
+        self.objects
     }
 }
 
-impl<T: Hittable> Hittable for HittableList<T> {
+impl Default for HittableList {
+    fn default() -> Self {
+        HittableList::new()
+    }
+}
+
+impl Hittable for HittableList {
     fn hit<'a>(
         &self,
         r: &crate::ray::Ray,
@@ -32,6 +59,8 @@ impl<T: Hittable> Hittable for HittableList<T> {
     ) -> &'a mut HitRecord {
         let mut temp_rec = HitRecord::default();
         let mut closest_so_far = ray_t.max;
+        // Nested lists may be handed a record left over from a sibling
+        rec.is_hit = false;
 
         for obj in &self.objects {
             let new_rec = obj.hit(r, &Interval::new(ray_t.min, closest_so_far), &mut temp_rec);
@@ -43,4 +72,40 @@ impl<T: Hittable> Hittable for HittableList<T> {
 
         rec
     }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        color::Color,
+        material::MaterialType,
+        objects::sphere::Sphere,
+        ray::Ray,
+        utils::INFINITY,
+        vec3::{Point3, Vec3},
+    };
+
+    #[test]
+    fn mixes_shapes_and_nested_lists() {
+        let mat = MaterialType::lambertian(Color::new(0.5, 0.5, 0.5));
+        let mut inner = HittableList::new();
+        inner.add_obj(Sphere::new(Point3::new(0.0, 0.0, -3.0), 1.0, mat.clone()));
+
+        let mut world = HittableList::new();
+        world.add_obj(Sphere::new(Point3::new(0.0, 0.0, -10.0), 1.0, mat));
+        world.add_obj(inner);
+        assert_eq!(world.len(), 2);
+        assert_eq!(world.bounding_box().z.max, -2.0);
+
+        let r = Ray::new(Point3::default(), Vec3::new(0.0, 0.0, -1.0));
+        let mut rec = HitRecord::default();
+        let rec = world.hit(&r, &Interval::new(0.001, INFINITY), &mut rec);
+        assert!(rec.is_hit);
+        assert_eq!(rec.t, 2.0);
+    }
 }
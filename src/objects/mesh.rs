@@ -0,0 +1,276 @@
+use std::sync::Arc;
+
+use crate::{
+    aabb::Aabb,
+    color::Color,
+    hittable::{HitRecord, Hittable},
+    interval::Interval,
+    material::MaterialType,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{bvh::BvhNode, hittable_list::HittableList, triangle};
+
+// Vertex buffers shared by every face of a mesh. Normals, UVs and colours,
+// when present, are indexed the same way as positions.
+#[derive(Default)]
+pub struct MeshData {
+    pub positions: Vec<Point3>,
+    pub normals: Option<Vec<Vec3>>,
+    pub uvs: Option<Vec<(f64, f64)>>,
+    pub colors: Option<Vec<Color>>,
+    pub faces: Vec<[usize; 3]>,
+}
+
+impl MeshData {
+    // Checks that every attribute buffer matches the positions and that all
+    // face indices are in range
+    pub fn validate(&self) -> Result<(), String> {
+        let count = self.positions.len();
+        if let Some(normals) = &self.normals {
+            if normals.len() != count {
+                return Err(format!("{} normals for {} vertices", normals.len(), count));
+            }
+        }
+        if let Some(uvs) = &self.uvs {
+            if uvs.len() != count {
+                return Err(format!("{} uvs for {} vertices", uvs.len(), count));
+            }
+        }
+        if let Some(colors) = &self.colors {
+            if colors.len() != count {
+                return Err(format!("{} colors for {} vertices", colors.len(), count));
+            }
+        }
+        for (i, face) in self.faces.iter().enumerate() {
+            if let Some(index) = face.iter().find(|&&index| index >= count) {
+                return Err(format!(
+                    "face {} references vertex {} of {}",
+                    i, index, count
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Mesh {
+    data: MeshData,
+    mat: MaterialType,
+}
+
+// A single face, referring back into the shared mesh buffers
+struct MeshTriangle {
+    mesh: Arc<Mesh>,
+    face: usize,
+    bbox: Aabb,
+}
+
+impl Hittable for MeshTriangle {
+    fn hit<'a>(&self, r: &Ray, ray_t: &Interval, rec: &'a mut HitRecord) -> &'a mut HitRecord {
+        let data = &self.mesh.data;
+        let [i0, i1, i2] = data.faces[self.face];
+        let (v0, v1, v2) = (
+            &data.positions[i0],
+            &data.positions[i1],
+            &data.positions[i2],
+        );
+        let Some((t, u, v)) = triangle::intersect(r, ray_t, v0, v1, v2) else {
+            rec.is_hit = false;
+            return rec;
+        };
+
+        rec.t = t;
+        rec.p = r.at(t);
+        // Front face is decided by the winding, shading uses the interpolated normal
+        let geometric = Vec3::unit_vector(&Vec3::cross(&(*v1 - *v0), &(*v2 - *v0)));
+        rec.self_face_normal(r, geometric);
+        if let Some(normals) = &data.normals {
+            let w = 1.0 - u - v;
+            let shading = w * normals[i0] + u * normals[i1] + v * normals[i2];
+            if !shading.near_zero() {
+                let shading = Vec3::unit_vector(&shading);
+                rec.normal = if rec.front_face { shading } else { -shading };
+            }
+        }
+        let (e1, e2) = (*v1 - *v0, *v2 - *v0);
+        (rec.u, rec.v) = (u, v);
+        (rec.dpdu, rec.dpdv) = (e1, e2);
+        if let Some(uvs) = &data.uvs {
+            let w = 1.0 - u - v;
+            rec.u = w * uvs[i0].0 + u * uvs[i1].0 + v * uvs[i2].0;
+            rec.v = w * uvs[i0].1 + u * uvs[i1].1 + v * uvs[i2].1;
+            // Invert the uv edges to express the position edges per unit u and v
+            let (du1, dv1) = (uvs[i1].0 - uvs[i0].0, uvs[i1].1 - uvs[i0].1);
+            let (du2, dv2) = (uvs[i2].0 - uvs[i0].0, uvs[i2].1 - uvs[i0].1);
+            let det = du1 * dv2 - dv1 * du2;
+            if det.abs() > 1e-12 {
+                rec.dpdu = (dv2 * e1 - dv1 * e2) / det;
+                rec.dpdv = (du1 * e2 - du2 * e1) / det;
+            }
+        }
+        rec.vertex_color = data
+            .colors
+            .as_ref()
+            .map(|colors| (1.0 - u - v) * colors[i0] + u * colors[i1] + v * colors[i2]);
+        rec.is_hit = true;
+        rec.mat = self.mesh.mat.clone();
+        rec
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+}
+
+// Indexed triangle mesh with its own BVH over the faces
+pub struct TriangleMesh {
+    faces: usize,
+    bvh: BvhNode,
+}
+
+impl TriangleMesh {
+    // Fails with the reason when the data does not pass validate
+    pub fn new(data: MeshData, mat: MaterialType) -> Result<Self, String> {
+        data.validate()?;
+
+        let faces = data.faces.len();
+        let mesh = Arc::new(Mesh { data, mat });
+        let mut list = HittableList::new();
+        for (face, &[i0, i1, i2]) in mesh.data.faces.iter().enumerate() {
+            let p = &mesh.data.positions;
+            let bbox = triangle::bounding_box(&p[i0], &p[i1], &p[i2]);
+            list.add_obj(MeshTriangle {
+                mesh: Arc::clone(&mesh),
+                face,
+                bbox,
+            });
+        }
+
+        Ok(TriangleMesh {
+            faces,
+            bvh: BvhNode::new(list),
+        })
+    }
+
+    pub fn face_count(&self) -> usize {
+        self.faces
+    }
+}
+
+impl Hittable for TriangleMesh {
+    fn hit<'a>(&self, r: &Ray, ray_t: &Interval, rec: &'a mut HitRecord) -> &'a mut HitRecord {
+        self.bvh.hit(r, ray_t, rec)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bvh.bounding_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{objects::triangle::Triangle, utils::INFINITY};
+
+    fn grey() -> MaterialType {
+        MaterialType::lambertian(Color::new(0.5, 0.5, 0.5))
+    }
+
+    // Unit square in the z = 0 plane split into two triangles
+    fn square(normals: Option<Vec<Vec3>>) -> MeshData {
+        MeshData {
+            positions: vec![
+                Point3::new(0.0, 0.0, 0.0),
+                Point3::new(1.0, 0.0, 0.0),
+                Point3::new(1.0, 1.0, 0.0),
+                Point3::new(0.0, 1.0, 0.0),
+            ],
+            normals,
+            uvs: None,
+            colors: None,
+            faces: vec![[0, 1, 2], [0, 2, 3]],
+        }
+    }
+
+    fn cast<H: Hittable>(obj: &H, origin: Point3) -> HitRecord {
+        let r = Ray::new(origin, Vec3::new(0.0, 0.0, -1.0));
+        let mut rec = HitRecord::default();
+        obj.hit(&r, &Interval::new(0.001, INFINITY), &mut rec);
+        rec
+    }
+
+    #[test]
+    fn matches_individual_triangles() {
+        let data = square(None);
+        let mut list = HittableList::new();
+        for &[a, b, c] in &data.faces {
+            let p = &data.positions;
+            list.add_obj(Triangle::new(p[a], p[b], p[c], grey()));
+        }
+        let mesh = TriangleMesh::new(data, grey()).unwrap();
+        assert_eq!(mesh.face_count(), 2);
+
+        for origin in [
+            Point3::new(0.7, 0.2, 3.0),
+            Point3::new(0.2, 0.7, 3.0),
+            Point3::new(1.5, 0.5, 3.0),
+        ] {
+            let a = cast(&mesh, origin);
+            let b = cast(&list, origin);
+            assert_eq!(a.is_hit, b.is_hit);
+            if a.is_hit {
+                assert_eq!(a.t, b.t);
+                assert_eq!(a.normal.z(), b.normal.z());
+            }
+        }
+    }
+
+    #[test]
+    fn smooth_shading_interpolates_normals() {
+        let tilt = Vec3::unit_vector(&Vec3::new(1.0, 0.0, 1.0));
+        let up = Vec3::new(0.0, 0.0, 1.0);
+        let mesh = TriangleMesh::new(square(Some(vec![up, tilt, tilt, up])), grey()).unwrap();
+
+        // Halfway across the square the normal is between the two
+        let rec = cast(&mesh, Point3::new(0.5, 0.25, 1.0));
+        assert!(rec.is_hit && rec.front_face);
+        assert!(rec.normal.x() > 0.0 && rec.normal.x() < tilt.x());
+        assert!((rec.normal.length() - 1.0).abs() < 1e-12);
+
+        let rec = cast(&mesh, Point3::new(0.0001, 0.5, 1.0));
+        assert!(rec.normal.x() < 1e-3);
+    }
+
+    #[test]
+    fn tangents_follow_texture_coordinates() {
+        // Texture rotated a quarter turn: u runs up the square, v to the left
+        let mut data = square(None);
+        data.uvs = Some(vec![(0.0, 1.0), (0.0, 0.0), (1.0, 0.0), (1.0, 1.0)]);
+        let mesh = TriangleMesh::new(data, grey()).unwrap();
+        for origin in [Point3::new(0.7, 0.2, 1.0), Point3::new(0.2, 0.7, 1.0)] {
+            let rec = cast(&mesh, origin);
+            assert!(
+                (rec.u - origin.y()).abs() < 1e-12 && (rec.v - (1.0 - origin.x())).abs() < 1e-12
+            );
+            assert!((rec.dpdu - Vec3::new(0.0, 1.0, 0.0)).near_zero());
+            assert!((rec.dpdv - Vec3::new(-1.0, 0.0, 0.0)).near_zero());
+        }
+    }
+
+    #[test]
+    fn rejects_out_of_range_faces() {
+        let mut data = square(None);
+        data.faces.push([0, 1, 4]);
+        assert_eq!(
+            data.validate(),
+            Err("face 2 references vertex 4 of 4".to_string())
+        );
+        let data = square(Some(vec![Vec3::default()]));
+        assert_eq!(
+            TriangleMesh::new(data, grey()).err(),
+            Some("1 normals for 4 vertices".to_string())
+        );
+    }
+}
@@ -1,2 +1,15 @@
+pub mod bvh;
+pub mod cone;
+pub mod constant_medium;
+pub mod cuboid;
+pub mod cylinder;
+pub mod disk;
 pub mod hittable_list;
+pub mod mesh;
+pub mod moving;
+pub mod plane;
+pub mod quad;
 pub mod sphere;
+pub mod torus;
+pub mod transformed;
+pub mod triangle;
@@ -0,0 +1,81 @@
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    interval::Interval,
+    ray::Ray,
+    vec3::Vec3,
+};
+
+// Slides any object along a straight line: it is in place at time 0 and
+// offset by `motion` at time 1
+pub struct Moving<H: Hittable> {
+    object: H,
+    motion: Vec3,
+    bbox: Aabb,
+}
+
+impl<H: Hittable> Moving<H> {
+    pub fn new(object: H, motion: Vec3) -> Self {
+        let start = object.bounding_box();
+        let shift = |i: &Interval, by: f64| Interval::new(i.min + by, i.max + by);
+        let end = Aabb::new(
+            shift(&start.x, motion.x()),
+            shift(&start.y, motion.y()),
+            shift(&start.z, motion.z()),
+        );
+        Moving {
+            object,
+            motion,
+            bbox: Aabb::enclose(&start, &end),
+        }
+    }
+}
+
+impl<H: Hittable> Hittable for Moving<H> {
+    fn hit<'a>(&self, r: &Ray, ray_t: &Interval, rec: &'a mut HitRecord) -> &'a mut HitRecord {
+        // Move the ray back instead of the object forward
+        let offset = r.time() * self.motion;
+        let local = Ray::with_time(*r.origin() - offset, *r.dir(), r.time());
+        if self.object.hit(&local, ray_t, rec).is_hit {
+            rec.p += offset;
+        }
+        rec
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        color::Color, material::MaterialType, objects::cuboid::Cuboid, utils::INFINITY,
+        vec3::Point3,
+    };
+
+    #[test]
+    fn follows_ray_time() {
+        let moving = Moving::new(
+            Cuboid::new(
+                Point3::new(-0.5, -0.5, -0.5),
+                Point3::new(0.5, 0.5, 0.5),
+                MaterialType::lambertian(Color::new(0.5, 0.5, 0.5)),
+            ),
+            Vec3::new(0.0, 2.0, 0.0),
+        );
+        let cast = |time: f64| {
+            let r = Ray::with_time(Point3::new(0.0, 1.0, 5.0), Vec3::new(0.0, 0.0, -1.0), time);
+            let mut rec = HitRecord::default();
+            moving.hit(&r, &Interval::new(0.001, INFINITY), &mut rec);
+            rec
+        };
+
+        assert!(!cast(0.0).is_hit);
+        let rec = cast(0.5);
+        assert!(rec.is_hit);
+        assert!((rec.p - Point3::new(0.0, 1.0, 0.5)).near_zero());
+        assert!(moving.bounding_box().y.contains(2.5));
+    }
+}
@@ -0,0 +1,135 @@
+use crate::{
+    aabb::{self, Aabb},
+    hittable::{HitRecord, Hittable},
+    interval::Interval,
+    material::MaterialType,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+// Orthonormal tangent and bitangent completing a right-handed frame around
+// the unit `normal`. The tangent follows +x whenever the normal allows it.
+pub(crate) fn tangent_frame(normal: &Vec3) -> (Vec3, Vec3) {
+    let axis = if normal.x().abs() > 0.9 {
+        Vec3::new(0.0, 1.0, 0.0)
+    } else {
+        Vec3::new(1.0, 0.0, 0.0)
+    };
+    let tangent = Vec3::unit_vector(&(axis - Vec3::dot(&axis, normal) * *normal));
+    let bitangent = Vec3::cross(normal, &tangent);
+    (tangent, bitangent)
+}
+
+// Distance along the ray to the plane through `point`, if it is in range
+pub(crate) fn intersect_plane(
+    r: &Ray,
+    ray_t: &Interval,
+    point: &Point3,
+    normal: &Vec3,
+) -> Option<f64> {
+    let denom = Vec3::dot(normal, r.dir());
+    if denom.abs() < 1e-8 {
+        return None;
+    }
+    let t = Vec3::dot(normal, &(*point - *r.origin())) / denom;
+    ray_t.surrounds(t).then_some(t)
+}
+
+// Infinite plane through `point`. The UVs are world distances along the
+// tangent frame, so textures tile once per unit.
+pub struct Plane {
+    point: Point3,
+    normal: Vec3,
+    tangent: Vec3,
+    bitangent: Vec3,
+    mat: MaterialType,
+}
+
+impl Plane {
+    pub fn new(point: Point3, normal: Vec3, mat: MaterialType) -> Self {
+        let normal = Vec3::unit_vector(&normal);
+        let (tangent, bitangent) = tangent_frame(&normal);
+        Plane {
+            point,
+            normal,
+            tangent,
+            bitangent,
+            mat,
+        }
+    }
+}
+
+impl Hittable for Plane {
+    fn hit<'a>(&self, r: &Ray, ray_t: &Interval, rec: &'a mut HitRecord) -> &'a mut HitRecord {
+        let Some(t) = intersect_plane(r, ray_t, &self.point, &self.normal) else {
+            rec.is_hit = false;
+            return rec;
+        };
+
+        rec.t = t;
+        rec.p = r.at(t);
+        rec.self_face_normal(r, self.normal);
+        let offset = rec.p - self.point;
+        rec.u = Vec3::dot(&offset, &self.tangent);
+        rec.v = Vec3::dot(&offset, &self.bitangent);
+        (rec.dpdu, rec.dpdv) = (self.tangent, self.bitangent);
+        rec.is_hit = true;
+        rec.mat = self.mat.clone();
+        rec.vertex_color = None;
+        rec
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        aabb::UNIVERSE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{color::Color, utils::INFINITY};
+
+    #[test]
+    fn ground_plane() {
+        let plane = Plane::new(
+            Point3::new(0.0, -1.0, 0.0),
+            Vec3::new(0.0, 2.0, 0.0),
+            MaterialType::lambertian(Color::new(0.5, 0.5, 0.5)),
+        );
+        let r = Ray::new(Point3::new(3.0, 1.0, 0.0), Vec3::new(0.0, -1.0, -1.0));
+        let mut rec = HitRecord::default();
+        plane.hit(&r, &Interval::new(0.001, INFINITY), &mut rec);
+        assert!(rec.is_hit && rec.front_face);
+        assert!((rec.t - 2.0).abs() < 1e-12);
+        assert_eq!(rec.normal.y(), 1.0);
+        assert!((rec.u - 3.0).abs() < 1e-12);
+        assert!((rec.v - 2.0).abs() < 1e-12);
+
+        // From below, and parallel to the plane
+        let r = Ray::new(Point3::new(0.0, -3.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        plane.hit(&r, &Interval::new(0.001, INFINITY), &mut rec);
+        assert!(rec.is_hit && !rec.front_face);
+        assert_eq!(rec.normal.y(), -1.0);
+        let r = Ray::new(Point3::new(0.0, 1.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert!(
+            !plane
+                .hit(&r, &Interval::new(0.001, INFINITY), &mut rec)
+                .is_hit
+        );
+    }
+
+    #[test]
+    fn frame_is_orthonormal() {
+        for n in [
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::unit_vector(&Vec3::new(1.0, 2.0, 3.0)),
+        ] {
+            let (t, b) = tangent_frame(&n);
+            assert!(Vec3::dot(&t, &n).abs() < 1e-12);
+            assert!(Vec3::dot(&t, &b).abs() < 1e-12);
+            assert!((b.length() - 1.0).abs() < 1e-12);
+            assert!((Vec3::cross(&t, &b) - n).near_zero());
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    interval::Interval,
+    material::MaterialType,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::plane::intersect_plane;
+
+// Parallelogram spanned by the edges `u` and `v` from the corner `q`. The
+// front face is the side `u x v` points to, and (u, v) in the hit record
+// are the coordinates along the two edges.
+pub struct Quad {
+    q: Point3,
+    u: Vec3,
+    v: Vec3,
+    // n / (n . n), turns cross products into edge coordinates
+    w: Vec3,
+    normal: Vec3,
+    mat: MaterialType,
+    bbox: Aabb,
+}
+
+impl Quad {
+    pub fn new(q: Point3, u: Vec3, v: Vec3, mat: MaterialType) -> Self {
+        let n = Vec3::cross(&u, &v);
+        let bbox = Aabb::enclose(
+            &Aabb::from_points(q, q + u + v),
+            &Aabb::from_points(q + u, q + v),
+        );
+        Quad {
+            q,
+            u,
+            v,
+            w: n / Vec3::dot(&n, &n),
+            normal: Vec3::unit_vector(&n),
+            mat,
+            bbox: bbox.pad_to_minimums(),
+        }
+    }
+}
+
+impl Hittable for Quad {
+    fn hit<'a>(&self, r: &Ray, ray_t: &Interval, rec: &'a mut HitRecord) -> &'a mut HitRecord {
+        let Some(t) = intersect_plane(r, ray_t, &self.q, &self.normal) else {
+            rec.is_hit = false;
+            return rec;
+        };
+
+        let p = r.at(t);
+        let planar = p - self.q;
+        let alpha = Vec3::dot(&self.w, &Vec3::cross(&planar, &self.v));
+        let beta = Vec3::dot(&self.w, &Vec3::cross(&self.u, &planar));
+        let unit = Interval::new(0.0, 1.0);
+        if !unit.contains(alpha) || !unit.contains(beta) {
+            rec.is_hit = false;
+            return rec;
+        }
+
+        rec.t = t;
+        rec.p = p;
+        rec.self_face_normal(r, self.normal);
+        rec.u = alpha;
+        rec.v = beta;
+        (rec.dpdu, rec.dpdv) = (self.u, self.v);
+        rec.is_hit = true;
+        rec.mat = self.mat.clone();
+        rec.vertex_color = None;
+        rec
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{color::Color, utils::INFINITY};
+
+    fn cast(quad: &Quad, origin: Point3) -> HitRecord {
+        let r = Ray::new(origin, Vec3::new(0.0, 0.0, -1.0));
+        let mut rec = HitRecord::default();
+        quad.hit(&r, &Interval::new(0.001, INFINITY), &mut rec);
+        rec
+    }
+
+    #[test]
+    fn edge_coordinates() {
+        // Slanted parallelogram in the z = 0 plane
+        let quad = Quad::new(
+            Point3::new(1.0, 1.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(1.0, 4.0, 0.0),
+            MaterialType::lambertian(Color::new(0.5, 0.5, 0.5)),
+        );
+
+        let rec = cast(&quad, Point3::new(2.5, 3.0, 5.0));
+        assert!(rec.is_hit && rec.front_face);
+        assert_eq!(rec.normal.z(), 1.0);
+        assert!((rec.u - 0.5).abs() < 1e-12);
+        assert!((rec.v - 0.5).abs() < 1e-12);
+
+        // Inside the bounding box but outside the slanted edges
+        assert!(!cast(&quad, Point3::new(1.2, 4.5, 5.0)).is_hit);
+        assert!(!cast(&quad, Point3::new(0.0, 0.0, 5.0)).is_hit);
+        assert!(quad.bounding_box().z.size() > 0.0);
+    }
+}
@@ -1,23 +1,52 @@
 use crate::{
+    aabb::Aabb,
     hittable::{HitRecord, Hittable},
     interval::Interval,
     material::MaterialType,
     ray::Ray,
-    vec3::Point3,
+    utils::PI,
+    vec3::{Point3, Vec3},
 };
 
+use super::plane::tangent_frame;
+
+// Maps a point on the unit sphere to (u, v): u is the angle around the y axis
+// starting from -x, v runs from the south pole to the north pole
+fn sphere_uv(p: &Point3) -> (f64, f64) {
+    let theta = f64::acos(-p.y());
+    let phi = f64::atan2(-p.z(), p.x()) + PI;
+    (phi / (2.0 * PI), theta / PI)
+}
+
+// Derivatives of the surface point along u and v for the unit normal `n`.
+// At the poles, where u is undefined, any frame in the tangent plane does.
+fn sphere_tangents(n: &Vec3, radius: f64) -> (Vec3, Vec3) {
+    let s = f64::sqrt(n.x() * n.x() + n.z() * n.z());
+    if s < 1e-12 {
+        let (tangent, bitangent) = tangent_frame(n);
+        return (2.0 * PI * radius * tangent, PI * radius * bitangent);
+    }
+    let dpdu = 2.0 * PI * radius * Vec3::new(n.z(), 0.0, -n.x());
+    let dpdv = PI * radius * Vec3::new(-n.x() * n.y() / s, s, -n.z() * n.y() / s);
+    (dpdu, dpdv)
+}
+
 pub struct Sphere {
     center: Point3,
     radius: f64,
     mat: MaterialType,
+    bbox: Aabb,
 }
 
 impl Sphere {
     pub fn new(center: Point3, radius: f64, mat: MaterialType) -> Self {
+        let radius = f64::max(0.0, radius);
+        let rvec = Vec3::new(radius, radius, radius);
         Sphere {
             center,
             mat,
-            radius: f64::max(0.0, radius),
+            radius,
+            bbox: Aabb::from_points(center - rvec, center + rvec),
         }
     }
 }
@@ -50,9 +79,53 @@ impl Hittable for Sphere {
         rec.p = r.at(rec.t);
         let outward_normal = (rec.p - self.center) / self.radius;
         rec.self_face_normal(r, outward_normal);
+        (rec.u, rec.v) = sphere_uv(&outward_normal);
+        (rec.dpdu, rec.dpdv) = sphere_tangents(&outward_normal, self.radius);
         rec.is_hit = true;
         rec.mat = self.mat.clone();
+        rec.vertex_color = None;
+
+        rec
+    }
 
-        return rec;
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uv_follows_latitude_and_longitude() {
+        let uv = |x: f64, y: f64, z: f64| sphere_uv(&Point3::new(x, y, z));
+        assert_eq!(uv(-1.0, 0.0, 0.0), (0.0, 0.5));
+        assert_eq!(uv(0.0, 0.0, 1.0), (0.25, 0.5));
+        assert_eq!(uv(1.0, 0.0, 0.0), (0.5, 0.5));
+        assert_eq!(uv(0.0, 1.0, 0.0).1, 1.0);
+        assert_eq!(uv(0.0, -1.0, 0.0).1, 0.0);
+    }
+
+    #[test]
+    fn tangents_follow_uv() {
+        // Small steps along the tangents change u and v at the expected rate
+        for n in [
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::unit_vector(&Vec3::new(0.3, -0.5, -0.8)),
+            Vec3::unit_vector(&Vec3::new(-2.0, 1.5, 0.1)),
+        ] {
+            let (dpdu, dpdv) = sphere_tangents(&n, 2.0);
+            assert!(Vec3::dot(&dpdu, &n).abs() < 1e-12);
+            assert!(Vec3::dot(&dpdv, &n).abs() < 1e-12);
+            let (u, v) = sphere_uv(&n);
+            let step = |d: Vec3| sphere_uv(&Vec3::unit_vector(&(2.0 * n + 1e-6 * d)));
+            let (u1, v1) = step(dpdu);
+            assert!((u1 - u - 1e-6).abs() < 1e-9 && (v1 - v).abs() < 1e-9);
+            let (u2, v2) = step(dpdv);
+            assert!((u2 - u).abs() < 1e-9 && (v2 - v - 1e-6).abs() < 1e-9);
+        }
+        let (dpdu, dpdv) = sphere_tangents(&Vec3::new(0.0, 1.0, 0.0), 1.0);
+        assert!(!dpdu.near_zero() && !dpdv.near_zero());
     }
 }
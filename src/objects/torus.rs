@@ -0,0 +1,183 @@
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    interval::Interval,
+    material::MaterialType,
+    polynomial::solve_quartic,
+    ray::Ray,
+    utils::PI,
+    vec3::{Point3, Vec3},
+};
+
+use super::cylinder::{azimuth, azimuth_tangent, fill_record, Nearest};
+
+// Ring around the y axis through `center`: a tube of `minor_radius` swept
+// along a circle of `major_radius` in the xz plane. u runs around the ring,
+// v around the tube starting from its outer equator.
+pub struct Torus {
+    center: Point3,
+    major_radius: f64,
+    minor_radius: f64,
+    mat: MaterialType,
+    bbox: Aabb,
+}
+
+impl Torus {
+    pub fn new(center: Point3, major_radius: f64, minor_radius: f64, mat: MaterialType) -> Self {
+        let major_radius = f64::max(0.0, major_radius);
+        let minor_radius = f64::max(0.0, minor_radius);
+        let outer = major_radius + minor_radius;
+        let extent = Vec3::new(outer, minor_radius, outer);
+        Torus {
+            center,
+            major_radius,
+            minor_radius,
+            mat,
+            bbox: Aabb::from_points(center - extent, center + extent),
+        }
+    }
+}
+
+impl Hittable for Torus {
+    fn hit<'a>(&self, r: &Ray, ray_t: &Interval, rec: &'a mut HitRecord) -> &'a mut HitRecord {
+        // The quartic is badly conditioned for far away origins, so solve
+        // with a unit direction from the point on the ray nearest the center
+        let length = r.dir().length();
+        let d = *r.dir() / length;
+        let shift = Vec3::dot(&(self.center - *r.origin()), &d);
+        let o = *r.origin() + shift * d - self.center;
+
+        // (|p|^2 + R^2 - r^2)^2 = 4 R^2 (x^2 + z^2) for p = o + s d
+        let r2 = self.major_radius * self.major_radius;
+        let b = Vec3::dot(&o, &d);
+        let k = o.length_squared() + r2 - self.minor_radius * self.minor_radius;
+        let roots = solve_quartic(
+            1.0,
+            4.0 * b,
+            4.0 * b * b + 2.0 * k - 4.0 * r2 * (d.x() * d.x() + d.z() * d.z()),
+            4.0 * b * k - 8.0 * r2 * (o.x() * d.x() + o.z() * d.z()),
+            k * k - 4.0 * r2 * (o.x() * o.x() + o.z() * o.z()),
+        );
+
+        let best = roots
+            .into_iter()
+            .map(|s| (s + shift) / length)
+            .find(|&t| ray_t.surrounds(t))
+            .map(|t| {
+                let p = *r.origin() + t * *r.dir() - self.center;
+                // Direction from the core circle to the surface point
+                let rho = f64::sqrt(p.x() * p.x() + p.z() * p.z());
+                let outward = if rho > 0.0 {
+                    Vec3::new(p.x(), 0.0, p.z()) / rho
+                } else {
+                    Vec3::new(1.0, 0.0, 0.0)
+                };
+                let core = if rho > 0.0 {
+                    self.major_radius * outward
+                } else {
+                    Vec3::default()
+                };
+                let normal = Vec3::unit_vector(&(p - core));
+                let tube = f64::atan2(p.y(), rho - self.major_radius);
+                let v = (tube + 2.0 * PI) % (2.0 * PI) / (2.0 * PI);
+                // Around the tube the point turns in the plane of the axis
+                let around = -p.y() * outward + Vec3::new(0.0, rho - self.major_radius, 0.0);
+                Nearest {
+                    t,
+                    normal,
+                    uv: (azimuth(p.x(), p.z()), v),
+                    tangents: (azimuth_tangent(p.x(), p.z()), 2.0 * PI * around),
+                }
+            });
+
+        fill_record(r, best, &self.mat, rec)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        color::Color,
+        utils::{seed_thread_rng, INFINITY},
+    };
+
+    fn torus() -> Torus {
+        Torus::new(
+            Point3::new(1.0, 2.0, 3.0),
+            2.0,
+            0.5,
+            MaterialType::lambertian(Color::new(0.5, 0.5, 0.5)),
+        )
+    }
+
+    fn cast(origin: Point3, dir: Vec3) -> HitRecord {
+        let mut rec = HitRecord::default();
+        torus().hit(
+            &Ray::new(origin + Vec3::new(1.0, 2.0, 3.0), dir),
+            &Interval::new(0.001, INFINITY),
+            &mut rec,
+        );
+        rec
+    }
+
+    #[test]
+    fn known_intersections() {
+        // Along the x axis the ray crosses the tube at x = 2.5, 1.5, -1.5, -2.5
+        let rec = cast(Point3::new(10.0, 0.0, 0.0), Vec3::new(-2.0, 0.0, 0.0));
+        assert!(rec.is_hit && rec.front_face);
+        assert!((rec.t - 3.75).abs() < 1e-9);
+        assert!((rec.normal.x() - 1.0).abs() < 1e-9);
+        assert!(rec.v.abs() < 1e-9 || (rec.v - 1.0).abs() < 1e-9);
+
+        // From the hole the inner wall is a front face
+        let rec = cast(Point3::default(), Vec3::new(0.0, 0.0, 1.0));
+        assert!(rec.is_hit && rec.front_face);
+        assert!((rec.t - 1.5).abs() < 1e-9);
+        assert!((rec.normal.z() + 1.0).abs() < 1e-9);
+        assert!((rec.v - 0.5).abs() < 1e-9);
+
+        // Straight down onto the top of the tube
+        let rec = cast(Point3::new(0.0, 5.0, -2.0), Vec3::new(0.0, -1.0, 0.0));
+        assert!(rec.is_hit && rec.front_face);
+        assert!((rec.t - 4.5).abs() < 1e-9);
+        assert!((rec.normal.y() - 1.0).abs() < 1e-9);
+
+        // Through the hole, and past the outside
+        assert!(!cast(Point3::new(0.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0)).is_hit);
+        assert!(!cast(Point3::new(0.0, 0.6, 10.0), Vec3::new(0.0, 0.0, -1.0)).is_hit);
+    }
+
+    #[test]
+    fn grazing_and_distant_rays() {
+        seed_thread_rng(15);
+        // Tangent to the top of the tube from far away
+        let far = cast(Point3::new(1e4, 0.499, -2.0), Vec3::new(-1.0, 0.0, 0.0));
+        assert!(far.is_hit);
+        let p = far.p - Point3::new(1.0, 2.0, 3.0);
+        let rho = f64::sqrt(p.x() * p.x() + p.z() * p.z());
+        let dist = f64::sqrt((rho - 2.0) * (rho - 2.0) + p.y() * p.y());
+        assert!((dist - 0.5).abs() < 1e-6);
+
+        // Random rays from outside land on the surface
+        for _ in 0..500 {
+            let origin = Vec3::random_unit_vector() * 20.0;
+            let target = Vec3::random_min_max(-2.5, 2.5);
+            let rec = cast(origin, target - origin);
+            if rec.is_hit {
+                let p = rec.p - Point3::new(1.0, 2.0, 3.0);
+                let rho = f64::sqrt(p.x() * p.x() + p.z() * p.z());
+                let dist = f64::sqrt((rho - 2.0) * (rho - 2.0) + p.y() * p.y());
+                assert!((dist - 0.5).abs() < 1e-9);
+                assert!(rec.front_face);
+                // Both tangents lie in the surface
+                assert!(Vec3::dot(&rec.dpdu, &rec.normal).abs() < 1e-9 * rec.dpdu.length());
+                assert!(Vec3::dot(&rec.dpdv, &rec.normal).abs() < 1e-9 * rec.dpdv.length());
+            }
+        }
+    }
+}
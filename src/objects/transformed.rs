@@ -0,0 +1,178 @@
+use crate::{
+    aabb::{self, Aabb},
+    hittable::{HitRecord, Hittable},
+    interval::Interval,
+    ray::Ray,
+    vec3::{Mat4, Point3, Vec3},
+};
+
+// Places an object with an affine transform. Rays are taken into object
+// space and hits brought back out, so wrapping an `Arc` of a mesh instances
+// it without copying its triangles.
+pub struct Transformed<H: Hittable> {
+    object: H,
+    to_world: Mat4,
+    to_object: Mat4,
+    // Inverse transpose, keeps normals perpendicular to the surface
+    normal_to_world: Mat4,
+    bbox: Aabb,
+}
+
+impl<H: Hittable> Transformed<H> {
+    // Panics when the transform cannot be inverted, e.g. a zero scale
+    pub fn new(object: H, transform: Mat4) -> Self {
+        let to_object = transform.inverse().expect("Transform must be invertible");
+        let bbox = transform_box(&object.bounding_box(), &transform);
+        Transformed {
+            object,
+            to_world: transform,
+            to_object,
+            normal_to_world: to_object.transpose(),
+            bbox,
+        }
+    }
+}
+
+// Box around the eight transformed corners
+fn transform_box(bbox: &Aabb, m: &Mat4) -> Aabb {
+    let bounded = (0..3).all(|axis| {
+        let i = bbox.axis_interval(axis);
+        i.min.is_finite() && i.max.is_finite()
+    });
+    if !bounded {
+        // Infinite objects such as planes stay infinite
+        return aabb::UNIVERSE;
+    }
+
+    let mut result = aabb::EMPTY;
+    for corner in 0..8 {
+        let pick = |axis: usize| {
+            let i = bbox.axis_interval(axis);
+            if corner & (1 << axis) == 0 {
+                i.min
+            } else {
+                i.max
+            }
+        };
+        let p = m.transform_point(&Point3::new(pick(0), pick(1), pick(2)));
+        result = Aabb::enclose(&result, &Aabb::from_points(p, p));
+    }
+    result.pad_to_minimums()
+}
+
+impl<H: Hittable> Hittable for Transformed<H> {
+    fn hit<'a>(&self, r: &Ray, ray_t: &Interval, rec: &'a mut HitRecord) -> &'a mut HitRecord {
+        // The direction is not renormalised, so t means the same in both spaces
+        let local = Ray::with_time(
+            self.to_object.transform_point(r.origin()),
+            self.to_object.transform_vector(r.dir()),
+            r.time(),
+        );
+        if !self.object.hit(&local, ray_t, rec).is_hit {
+            return rec;
+        }
+
+        rec.p = self.to_world.transform_point(&rec.p);
+        rec.dpdu = self.to_world.transform_vector(&rec.dpdu);
+        rec.dpdv = self.to_world.transform_vector(&rec.dpdv);
+        let normal = self.normal_to_world.transform_vector(&rec.normal);
+        rec.normal = Vec3::unit_vector(&normal);
+        rec
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        color::Color,
+        material::MaterialType,
+        objects::{hittable_list::HittableList, quad::Quad, sphere::Sphere},
+        utils::INFINITY,
+    };
+
+    fn grey() -> MaterialType {
+        MaterialType::lambertian(Color::new(0.5, 0.5, 0.5))
+    }
+
+    fn cast<H: Hittable>(obj: &H, origin: Point3, dir: Vec3) -> HitRecord {
+        let mut rec = HitRecord::default();
+        obj.hit(
+            &Ray::new(origin, dir),
+            &Interval::new(0.001, INFINITY),
+            &mut rec,
+        );
+        rec
+    }
+
+    #[test]
+    fn scaled_sphere_becomes_ellipsoid() {
+        let transform =
+            Mat4::translation(Vec3::new(0.0, 0.0, -5.0)) * Mat4::scaling(Vec3::new(2.0, 1.0, 1.0));
+        let ellipsoid = Transformed::new(Sphere::new(Point3::default(), 1.0, grey()), transform);
+
+        let rec = cast(
+            &ellipsoid,
+            Point3::new(10.0, 0.0, -5.0),
+            Vec3::new(-1.0, 0.0, 0.0),
+        );
+        assert!(rec.is_hit && rec.front_face);
+        assert!((rec.t - 8.0).abs() < 1e-12);
+        assert!((rec.p - Point3::new(2.0, 0.0, -5.0)).near_zero());
+        assert!((rec.normal.x() - 1.0).abs() < 1e-12);
+
+        // Off-axis the normal tilts according to the squashed shape
+        let p = Point3::new(2.0 * 0.6, 0.8, -5.0);
+        let rec = cast(
+            &ellipsoid,
+            p + Vec3::new(0.0, 5.0, 0.0),
+            Vec3::new(0.0, -1.0, 0.0),
+        );
+        assert!((rec.p - p).length() < 1e-9);
+        let expected = Vec3::unit_vector(&Vec3::new(0.6 / 2.0, 0.8, 0.0));
+        assert!((rec.normal - expected).length() < 1e-9);
+
+        let bbox = ellipsoid.bounding_box();
+        assert!((bbox.x.max - 2.0).abs() < 1e-9 && (bbox.z.min + 6.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn shared_object_instanced_twice() {
+        let quad = Arc::new(Quad::new(
+            Point3::new(-0.5, -0.5, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            grey(),
+        ));
+        let mut list = HittableList::new();
+        list.add_obj(Transformed::new(
+            Arc::clone(&quad),
+            Mat4::translation(Vec3::new(-2.0, 0.0, 0.0)),
+        ));
+        list.add_obj(Transformed::new(
+            Arc::clone(&quad),
+            Mat4::translation(Vec3::new(2.0, 0.0, 0.0)) * Mat4::rotation_y(90.0),
+        ));
+
+        let rec = cast(
+            &list,
+            Point3::new(-2.0, 0.0, 5.0),
+            Vec3::new(0.0, 0.0, -1.0),
+        );
+        assert!(rec.is_hit && rec.front_face);
+        assert!((rec.normal.z() - 1.0).abs() < 1e-12);
+
+        // The rotated copy faces +x
+        let rec = cast(&list, Point3::new(5.0, 0.2, 0.2), Vec3::new(-1.0, 0.0, 0.0));
+        assert!(rec.is_hit && rec.front_face);
+        assert!((rec.normal.x() - 1.0).abs() < 1e-12);
+        assert!((rec.p.x() - 2.0).abs() < 1e-12);
+        assert!(!cast(&list, Point3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0)).is_hit);
+    }
+}
@@ -0,0 +1,169 @@
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    interval::Interval,
+    material::MaterialType,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+// Möller–Trumbore ray/triangle intersection, returning t and the barycentric
+// weights (u, v) of v1 and v2
+pub fn intersect(
+    r: &Ray,
+    ray_t: &Interval,
+    v0: &Point3,
+    v1: &Point3,
+    v2: &Point3,
+) -> Option<(f64, f64, f64)> {
+    let e1 = *v1 - *v0;
+    let e2 = *v2 - *v0;
+    let pvec = Vec3::cross(r.dir(), &e2);
+    let det = Vec3::dot(&e1, &pvec);
+    // Ray parallel to the triangle's plane
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    let inv_det = 1.0 / det;
+
+    let tvec = *r.origin() - *v0;
+    let u = Vec3::dot(&tvec, &pvec) * inv_det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let qvec = Vec3::cross(&tvec, &e1);
+    let v = Vec3::dot(r.dir(), &qvec) * inv_det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = Vec3::dot(&e2, &qvec) * inv_det;
+    if !ray_t.surrounds(t) {
+        return None;
+    }
+    Some((t, u, v))
+}
+
+pub fn bounding_box(v0: &Point3, v1: &Point3, v2: &Point3) -> Aabb {
+    let bbox = Aabb::enclose(&Aabb::from_points(*v0, *v1), &Aabb::from_points(*v0, *v2));
+    bbox.pad_to_minimums()
+}
+
+pub struct Triangle {
+    v0: Point3,
+    v1: Point3,
+    v2: Point3,
+    normal: Vec3,
+    mat: MaterialType,
+    bbox: Aabb,
+}
+
+impl Triangle {
+    // Vertices in counter-clockwise order when seen from the front
+    pub fn new(v0: Point3, v1: Point3, v2: Point3, mat: MaterialType) -> Self {
+        let normal = Vec3::unit_vector(&Vec3::cross(&(v1 - v0), &(v2 - v0)));
+        Triangle {
+            v0,
+            v1,
+            v2,
+            normal,
+            mat,
+            bbox: bounding_box(&v0, &v1, &v2),
+        }
+    }
+}
+
+impl Hittable for Triangle {
+    fn hit<'a>(&self, r: &Ray, ray_t: &Interval, rec: &'a mut HitRecord) -> &'a mut HitRecord {
+        let Some((t, u, v)) = intersect(r, ray_t, &self.v0, &self.v1, &self.v2) else {
+            rec.is_hit = false;
+            return rec;
+        };
+
+        rec.t = t;
+        rec.p = r.at(t);
+        rec.self_face_normal(r, self.normal);
+        // Barycentric weights of v1 and v2
+        rec.u = u;
+        rec.v = v;
+        (rec.dpdu, rec.dpdv) = (self.v1 - self.v0, self.v2 - self.v0);
+        rec.is_hit = true;
+        rec.mat = self.mat.clone();
+        rec.vertex_color = None;
+        rec
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{color::Color, utils::INFINITY};
+
+    fn unit_triangle() -> Triangle {
+        Triangle::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            MaterialType::lambertian(Color::new(0.5, 0.5, 0.5)),
+        )
+    }
+
+    fn cast(tri: &Triangle, origin: Point3, dir: Vec3) -> HitRecord {
+        let mut rec = HitRecord::default();
+        tri.hit(
+            &Ray::new(origin, dir),
+            &Interval::new(0.001, INFINITY),
+            &mut rec,
+        );
+        rec
+    }
+
+    #[test]
+    fn hits_inside_with_barycentrics() {
+        let r = Ray::new(Point3::new(0.25, 0.5, 2.0), Vec3::new(0.0, 0.0, -1.0));
+        let tri = unit_triangle();
+        let (t, u, v) =
+            intersect(&r, &Interval::new(0.0, INFINITY), &tri.v0, &tri.v1, &tri.v2).unwrap();
+        assert!((t - 2.0).abs() < 1e-12);
+        assert!((u - 0.25).abs() < 1e-12);
+        assert!((v - 0.5).abs() < 1e-12);
+
+        let rec = cast(&tri, Point3::new(0.25, 0.5, 2.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(rec.is_hit && rec.front_face);
+        assert_eq!(rec.normal.z(), 1.0);
+    }
+
+    #[test]
+    fn back_face_flips_normal() {
+        let rec = cast(
+            &unit_triangle(),
+            Point3::new(0.2, 0.2, -1.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        );
+        assert!(rec.is_hit && !rec.front_face);
+        assert_eq!(rec.normal.z(), -1.0);
+    }
+
+    #[test]
+    fn misses_outside_and_parallel() {
+        let tri = unit_triangle();
+        assert!(!cast(&tri, Point3::new(0.8, 0.8, 1.0), Vec3::new(0.0, 0.0, -1.0)).is_hit);
+        assert!(!cast(&tri, Point3::new(-0.1, 0.5, 1.0), Vec3::new(0.0, 0.0, -1.0)).is_hit);
+        assert!(!cast(&tri, Point3::new(0.2, 0.2, 1.0), Vec3::new(1.0, 0.0, 0.0)).is_hit);
+        // Behind the ray origin
+        assert!(!cast(&tri, Point3::new(0.2, 0.2, -1.0), Vec3::new(0.0, 0.0, -1.0)).is_hit);
+    }
+
+    #[test]
+    fn flat_triangle_has_padded_box() {
+        let bbox = unit_triangle().bounding_box();
+        assert!(bbox.z.size() > 0.0);
+        let r = Ray::new(Point3::new(0.2, 0.2, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(bbox.hit(&r, &Interval::new(0.001, INFINITY)));
+    }
+}
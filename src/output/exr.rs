@@ -0,0 +1,227 @@
+use std::io::{self, Write};
+
+use crate::image::Image;
+
+const MAGIC: [u8; 4] = [0x76, 0x2f, 0x31, 0x01];
+// Version 2, single-part scanline file
+const VERSION: [u8; 4] = [2, 0, 0, 0];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExrPixelType {
+    Half,
+    Float,
+}
+
+impl ExrPixelType {
+    fn id(&self) -> i32 {
+        match self {
+            ExrPixelType::Half => 1,
+            ExrPixelType::Float => 2,
+        }
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            ExrPixelType::Half => 2,
+            ExrPixelType::Float => 4,
+        }
+    }
+
+    fn push(&self, bytes: &mut Vec<u8>, value: f64) {
+        match self {
+            ExrPixelType::Half => bytes.extend(f32_to_half(value as f32).to_le_bytes()),
+            ExrPixelType::Float => bytes.extend((value as f32).to_le_bytes()),
+        }
+    }
+}
+
+// IEEE 754 binary16 conversion, rounding to nearest even
+pub fn f32_to_half(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exp = ((bits >> 23) & 0xff) as i32;
+    let mant = bits & 0x7f_ffff;
+
+    // Infinity and NaN, keeping NaN quiet
+    if exp == 0xff {
+        return sign | 0x7c00 | if mant != 0 { 0x200 } else { 0 };
+    }
+
+    let e = exp - 127 + 15;
+    if e >= 0x1f {
+        return sign | 0x7c00;
+    }
+
+    if e <= 0 {
+        // Too small even for a subnormal half
+        if e < -10 {
+            return sign;
+        }
+        let m = mant | 0x80_0000;
+        let shift = (14 - e) as u32;
+        let half_m = m >> shift;
+        let rem = m & ((1 << shift) - 1);
+        let halfway = 1 << (shift - 1);
+        let round = rem > halfway || (rem == halfway && half_m & 1 == 1);
+        return sign | (half_m + round as u32) as u16;
+    }
+
+    let half_m = mant >> 13;
+    let rem = mant & 0x1fff;
+    let mut h = (e as u32) << 10 | half_m;
+    // A carry out of the mantissa bumps the exponent, up to infinity
+    if rem > 0x1000 || (rem == 0x1000 && half_m & 1 == 1) {
+        h += 1;
+    }
+    sign | h as u16
+}
+
+fn attribute(header: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+    header.extend(name.as_bytes());
+    header.push(0);
+    header.extend(kind.as_bytes());
+    header.push(0);
+    header.extend((value.len() as i32).to_le_bytes());
+    header.extend(value);
+}
+
+fn box2i(xmax: i32, ymax: i32) -> Vec<u8> {
+    [0, 0, xmax, ymax]
+        .iter()
+        .flat_map(|v| v.to_le_bytes())
+        .collect()
+}
+
+// Uncompressed scanline OpenEXR with linear, unclamped R, G and B channels
+pub fn write_exr<W: Write>(out: &mut W, image: &Image, pixel_type: ExrPixelType) -> io::Result<()> {
+    let width = image.width();
+    let height = image.height();
+
+    // Channels must be listed in alphabetical order
+    let mut chlist = Vec::new();
+    for name in ["B", "G", "R"] {
+        chlist.extend(name.as_bytes());
+        chlist.push(0);
+        chlist.extend(pixel_type.id().to_le_bytes());
+        // pLinear and reserved bytes, then x and y sampling
+        chlist.extend([0, 0, 0, 0]);
+        chlist.extend(1i32.to_le_bytes());
+        chlist.extend(1i32.to_le_bytes());
+    }
+    chlist.push(0);
+
+    let window = box2i(width as i32 - 1, height as i32 - 1);
+    let mut header = Vec::new();
+    header.extend(MAGIC);
+    header.extend(VERSION);
+    attribute(&mut header, "channels", "chlist", &chlist);
+    attribute(&mut header, "compression", "compression", &[0]);
+    attribute(&mut header, "dataWindow", "box2i", &window);
+    attribute(&mut header, "displayWindow", "box2i", &window);
+    attribute(&mut header, "lineOrder", "lineOrder", &[0]);
+    attribute(
+        &mut header,
+        "pixelAspectRatio",
+        "float",
+        &1f32.to_le_bytes(),
+    );
+    attribute(&mut header, "screenWindowCenter", "v2f", &[0; 8]);
+    attribute(
+        &mut header,
+        "screenWindowWidth",
+        "float",
+        &1f32.to_le_bytes(),
+    );
+    header.push(0);
+
+    // One scanline per block: y, byte count, then each channel's samples in turn
+    let line_size = width * 3 * pixel_type.size();
+    let block_size = 8 + line_size;
+    let first_block = header.len() + 8 * height;
+    for y in 0..height {
+        header.extend(((first_block + y * block_size) as u64).to_le_bytes());
+    }
+    out.write_all(&header)?;
+
+    let mut block = Vec::with_capacity(block_size);
+    for (y, row) in image.rows().enumerate() {
+        block.clear();
+        block.extend((y as i32).to_le_bytes());
+        block.extend((line_size as i32).to_le_bytes());
+        for pixel_color in row {
+            pixel_type.push(&mut block, pixel_color.z());
+        }
+        for pixel_color in row {
+            pixel_type.push(&mut block, pixel_color.y());
+        }
+        for pixel_color in row {
+            pixel_type.push(&mut block, pixel_color.x());
+        }
+        out.write_all(&block)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    #[test]
+    fn half_conversion() {
+        assert_eq!(f32_to_half(0.0), 0x0000);
+        assert_eq!(f32_to_half(-0.0), 0x8000);
+        assert_eq!(f32_to_half(1.0), 0x3c00);
+        assert_eq!(f32_to_half(0.5), 0x3800);
+        assert_eq!(f32_to_half(-2.0), 0xc000);
+        assert_eq!(f32_to_half(0.1), 0x2e66);
+        assert_eq!(f32_to_half(65504.0), 0x7bff);
+        assert_eq!(f32_to_half(1e6), 0x7c00);
+        assert_eq!(f32_to_half(f32::INFINITY), 0x7c00);
+        assert_eq!(f32_to_half(2f32.powi(-24)), 0x0001);
+        assert_eq!(f32_to_half(2f32.powi(-14)), 0x0400);
+        assert_eq!(f32_to_half(2f32.powi(-26)), 0x0000);
+        assert_eq!(f32_to_half(f32::NAN) & 0x7c00, 0x7c00);
+        assert_ne!(f32_to_half(f32::NAN) & 0x3ff, 0);
+    }
+
+    fn find(haystack: &[u8], needle: &[u8]) -> usize {
+        haystack
+            .windows(needle.len())
+            .position(|w| w == needle)
+            .unwrap()
+    }
+
+    #[test]
+    fn offsets_point_at_scanlines() {
+        let mut image = Image::new(3, 2);
+        image.set(2, 1, Color::new(8.0, 0.5, 0.25));
+
+        for pixel_type in [ExrPixelType::Half, ExrPixelType::Float] {
+            let mut out = Vec::new();
+            write_exr(&mut out, &image, pixel_type).unwrap();
+            assert_eq!(&out[..4], &MAGIC);
+
+            let line_size = 3 * 3 * pixel_type.size();
+            let table = find(&out, b"screenWindowWidth\0float\0") + 24 + 4 + 4 + 1;
+            let offset = |y: usize| {
+                let b = &out[table + 8 * y..table + 8 * y + 8];
+                u64::from_le_bytes(b.try_into().unwrap()) as usize
+            };
+            assert_eq!(offset(0), table + 16);
+            assert_eq!(offset(1), offset(0) + 8 + line_size);
+            assert_eq!(out.len(), offset(1) + 8 + line_size);
+
+            // Last pixel of the second scanline, stored in B, G, R planes
+            let block = offset(1);
+            assert_eq!(&out[block..block + 4], &1i32.to_le_bytes());
+            let size = pixel_type.size();
+            let red = block + 8 + 2 * 3 * size + 2 * size;
+            let expected = match pixel_type {
+                ExrPixelType::Half => f32_to_half(8.0).to_le_bytes().to_vec(),
+                ExrPixelType::Float => 8f32.to_le_bytes().to_vec(),
+            };
+            assert_eq!(&out[red..red + size], &expected[..]);
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use std::io::{self, Write};
+
+use crate::{color::Color, image::Image};
+
+// Radiance RGBE: three 8-bit mantissas sharing one exponent byte. Scanlines
+// go top to bottom and each one is run-length encoded per channel when the
+// width allows it (8 to 32767 pixels).
+pub(crate) const MIN_RLE_WIDTH: usize = 8;
+pub(crate) const MAX_RLE_WIDTH: usize = 0x7fff;
+// Longest run or literal stretch a single count byte can describe
+const MAX_RUN: usize = 127;
+const MAX_LITERAL: usize = 128;
+
+pub fn write_hdr<W: Write>(out: &mut W, image: &Image) -> io::Result<()> {
+    let (width, height) = (image.width(), image.height());
+    let header = format!(
+        "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n",
+        height, width
+    );
+    out.write_all(header.as_bytes())?;
+
+    let mut bytes = Vec::with_capacity(width * height * 4);
+    for row in image.rows() {
+        let rgbe: Vec<[u8; 4]> = row.iter().map(to_rgbe).collect();
+        if !(MIN_RLE_WIDTH..=MAX_RLE_WIDTH).contains(&width) {
+            bytes.extend(rgbe.iter().flatten());
+            continue;
+        }
+        bytes.extend([2, 2, (width >> 8) as u8, (width & 0xff) as u8]);
+        for channel in 0..4 {
+            let values: Vec<u8> = rgbe.iter().map(|p| p[channel]).collect();
+            encode_runs(&mut bytes, &values);
+        }
+    }
+    out.write_all(&bytes)
+}
+
+pub fn to_rgbe(color: &Color) -> [u8; 4] {
+    let (r, g, b) = (
+        f64::max(color.x(), 0.0),
+        f64::max(color.y(), 0.0),
+        f64::max(color.z(), 0.0),
+    );
+    let v = r.max(g).max(b);
+    if v < 1e-32 || !v.is_finite() {
+        return [0, 0, 0, 0];
+    }
+    // v = m * 2^e with m in [0.5, 1)
+    let mut e = v.log2().floor() as i32 + 1;
+    if v / 2f64.powi(e) >= 1.0 {
+        e += 1;
+    }
+    let scale = 256.0 / 2f64.powi(e);
+    [
+        (r * scale) as u8,
+        (g * scale) as u8,
+        (b * scale) as u8,
+        (e + 128).clamp(0, 255) as u8,
+    ]
+}
+
+// Count bytes above 128 start a run of one repeated value, the others a
+// literal stretch of that many values
+fn encode_runs(bytes: &mut Vec<u8>, values: &[u8]) {
+    let mut i = 0;
+    while i < values.len() {
+        let run = values[i..]
+            .iter()
+            .take(MAX_RUN)
+            .take_while(|&&v| v == values[i])
+            .count();
+        if run > 2 {
+            bytes.extend([128 + run as u8, values[i]]);
+            i += run;
+            continue;
+        }
+
+        // Literals up to the next run worth encoding
+        let start = i;
+        while i < values.len() && i - start < MAX_LITERAL {
+            if i + 2 < values.len() && values[i] == values[i + 1] && values[i] == values[i + 2] {
+                break;
+            }
+            i += 1;
+        }
+        bytes.push((i - start) as u8);
+        bytes.extend(&values[start..i]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_rgbe() {
+        assert_eq!(to_rgbe(&Color::new(0.0, -1.0, 0.0)), [0, 0, 0, 0]);
+        assert_eq!(to_rgbe(&Color::new(1.0, 0.5, 0.25)), [128, 64, 32, 129]);
+        assert_eq!(to_rgbe(&Color::new(2.0, 0.0, 0.0)), [128, 0, 0, 130]);
+    }
+
+    #[test]
+    fn writes_rle_scanlines() {
+        // Flat areas become runs, the rest literals
+        let mut image = Image::new(40, 1);
+        for x in 20..40 {
+            image.set(x, 0, Color::new(x as f64 * 0.1, 1.0, 3.0));
+        }
+        let mut out = Vec::new();
+        write_hdr(&mut out, &image).unwrap();
+
+        let header = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 1 +X 40\n";
+        assert_eq!(&out[..header.len()], header);
+        assert_eq!(&out[header.len()..header.len() + 4], &[2, 2, 0, 40]);
+        assert!(out.len() < header.len() + 40 * 4);
+    }
+}
@@ -0,0 +1,69 @@
+use std::{
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::image::Image;
+
+use exr::ExrPixelType;
+
+pub mod exr;
+pub mod hdr;
+pub mod pfm;
+pub mod png;
+pub mod ppm;
+pub mod zlib;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    PpmAscii,
+    PpmBinary,
+    Pfm,
+    Hdr,
+    Png,
+    Exr(ExrPixelType),
+}
+
+impl OutputFormat {
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_ascii_lowercase().as_str() {
+            "ppm" => Some(OutputFormat::PpmBinary),
+            "pfm" => Some(OutputFormat::Pfm),
+            "hdr" => Some(OutputFormat::Hdr),
+            "png" => Some(OutputFormat::Png),
+            "exr" => Some(OutputFormat::Exr(ExrPixelType::Half)),
+            _ => None,
+        }
+    }
+
+    // Names accepted on the command line
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "p3" | "ppm-ascii" => Some(OutputFormat::PpmAscii),
+            "p6" | "ppm" => Some(OutputFormat::PpmBinary),
+            "pfm" => Some(OutputFormat::Pfm),
+            "hdr" => Some(OutputFormat::Hdr),
+            "png" => Some(OutputFormat::Png),
+            "exr" | "exr-half" => Some(OutputFormat::Exr(ExrPixelType::Half)),
+            "exr-float" => Some(OutputFormat::Exr(ExrPixelType::Float)),
+            _ => None,
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(OutputFormat::from_extension)
+    }
+}
+
+pub fn write_image<W: Write>(out: &mut W, image: &Image, format: OutputFormat) -> io::Result<()> {
+    match format {
+        OutputFormat::PpmAscii => ppm::write_ppm(out, image),
+        OutputFormat::PpmBinary => ppm::write_ppm_binary(out, image),
+        OutputFormat::Pfm => pfm::write_pfm(out, image),
+        OutputFormat::Hdr => hdr::write_hdr(out, image),
+        OutputFormat::Png => png::write_png(out, image),
+        OutputFormat::Exr(pixel_type) => exr::write_exr(out, image, pixel_type),
+    }
+}
@@ -0,0 +1,43 @@
+use std::io::{self, Write};
+
+use crate::image::Image;
+
+// Portable Float Map, linear radiance without gamma or clamping. Rows are
+// stored bottom to top and the negative scale marks little-endian floats.
+pub fn write_pfm<W: Write>(out: &mut W, image: &Image) -> io::Result<()> {
+    let header = format!("PF\n{} {}\n-1.0\n", image.width(), image.height());
+    out.write_all(header.as_bytes())?;
+
+    let mut bytes = Vec::with_capacity(image.pixels().len() * 12);
+    for row in image.rows().rev() {
+        for pixel_color in row {
+            bytes.extend((pixel_color.x() as f32).to_le_bytes());
+            bytes.extend((pixel_color.y() as f32).to_le_bytes());
+            bytes.extend((pixel_color.z() as f32).to_le_bytes());
+        }
+    }
+    out.write_all(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    #[test]
+    fn stores_unclamped_rows_bottom_up() {
+        let mut image = Image::new(1, 2);
+        image.set(0, 0, Color::new(4.5, 0.0, -1.0));
+        image.set(0, 1, Color::new(0.25, 0.5, 0.75));
+        let mut out = Vec::new();
+        write_pfm(&mut out, &image).unwrap();
+
+        let header = b"PF\n1 2\n-1.0\n";
+        assert_eq!(&out[..header.len()], header);
+        let floats: Vec<f32> = out[header.len()..]
+            .chunks(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        assert_eq!(floats, vec![0.25, 0.5, 0.75, 4.5, 0.0, -1.0]);
+    }
+}
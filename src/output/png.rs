@@ -0,0 +1,145 @@
+use std::io::{self, Write};
+
+use crate::{color::to_rgb_bytes, image::Image};
+
+use super::zlib::{compress, crc32_update};
+
+pub(crate) const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+pub(crate) fn write_chunk<W: Write>(out: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    out.write_all(&(data.len() as u32).to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+    let crc = crc32_update(crc32_update(0xffff_ffff, kind), data) ^ 0xffff_ffff;
+    out.write_all(&crc.to_be_bytes())
+}
+
+pub(crate) fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let pa = (p - a as i16).abs();
+    let pb = (p - b as i16).abs();
+    let pc = (p - c as i16).abs();
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+// Applies PNG filter `kind` to a scanline, given the unfiltered previous one
+fn filter_row(kind: u8, row: &[u8], prev: &[u8], bpp: usize, out: &mut Vec<u8>) {
+    out.push(kind);
+    for i in 0..row.len() {
+        let a = if i >= bpp { row[i - bpp] } else { 0 };
+        let b = prev[i];
+        let c = if i >= bpp { prev[i - bpp] } else { 0 };
+        let predicted = match kind {
+            0 => 0,
+            1 => a,
+            2 => b,
+            3 => ((a as u16 + b as u16) / 2) as u8,
+            _ => paeth(a, b, c),
+        };
+        out.push(row[i].wrapping_sub(predicted));
+    }
+}
+
+// Per-row filter selection using the minimum sum of absolute differences heuristic
+fn filter_scanlines(raw: &[u8], stride: usize, bpp: usize) -> Vec<u8> {
+    let mut filtered = Vec::with_capacity(raw.len() + raw.len() / usize::max(1, stride));
+    let zeros = vec![0u8; stride];
+    let mut candidate = Vec::with_capacity(stride + 1);
+    let mut best = Vec::with_capacity(stride + 1);
+
+    for (j, row) in raw.chunks(stride).enumerate() {
+        let prev = if j == 0 {
+            &zeros[..]
+        } else {
+            &raw[(j - 1) * stride..j * stride]
+        };
+        let mut best_score = u64::MAX;
+        for kind in 0..5 {
+            candidate.clear();
+            filter_row(kind, row, prev, bpp, &mut candidate);
+            let score = candidate[1..]
+                .iter()
+                .map(|&v| (v as i8).unsigned_abs() as u64)
+                .sum();
+            if score < best_score {
+                best_score = score;
+                std::mem::swap(&mut best, &mut candidate);
+            }
+        }
+        filtered.extend_from_slice(&best);
+    }
+    filtered
+}
+
+// 8-bit truecolor PNG, gamma corrected and clamped like the PPM output
+pub fn write_png<W: Write>(out: &mut W, image: &Image) -> io::Result<()> {
+    let raw: Vec<u8> = image.pixels().iter().flat_map(to_rgb_bytes).collect();
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend((image.width() as u32).to_be_bytes());
+    ihdr.extend((image.height() as u32).to_be_bytes());
+    // Bit depth 8, colour type 2 (RGB), default compression, filter and interlace
+    ihdr.extend([8, 2, 0, 0, 0]);
+
+    let idat = compress(&filter_scanlines(&raw, image.width() * 3, 3));
+
+    out.write_all(&SIGNATURE)?;
+    write_chunk(out, b"IHDR", &ihdr)?;
+    write_chunk(out, b"IDAT", &idat)?;
+    write_chunk(out, b"IEND", &[])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{color::Color, output::zlib::crc32};
+
+    #[test]
+    fn chunk_layout() {
+        let mut image = Image::new(4, 3);
+        image.set(1, 2, Color::new(1.0, 0.5, 0.25));
+        let mut out = Vec::new();
+        write_png(&mut out, &image).unwrap();
+
+        assert_eq!(&out[..8], &SIGNATURE);
+        // IHDR is always the first chunk, 13 bytes long
+        assert_eq!(&out[8..16], &[0, 0, 0, 13, b'I', b'H', b'D', b'R']);
+        assert_eq!(&out[16..20], &4u32.to_be_bytes());
+        assert_eq!(&out[20..24], &3u32.to_be_bytes());
+        assert_eq!(&out[29..33], &crc32(&out[12..29]).to_be_bytes());
+        assert_eq!(&out[out.len() - 12..out.len() - 4], b"\0\0\0\0IEND");
+    }
+
+    #[test]
+    fn filters_are_reversible() {
+        let raw: Vec<u8> = (0..60u32).map(|v| (v * 37 % 251) as u8).collect();
+        let prev: Vec<u8> = (0..60u32).map(|v| (v * 11 % 241) as u8).collect();
+        for kind in 0..5 {
+            let mut filtered = Vec::new();
+            filter_row(kind, &raw, &prev, 3, &mut filtered);
+            assert_eq!(filtered[0], kind);
+            // Undo the filter left to right
+            let mut recon = vec![0u8; raw.len()];
+            for i in 0..raw.len() {
+                let a = if i >= 3 { recon[i - 3] } else { 0 };
+                let b = prev[i];
+                let c = if i >= 3 { prev[i - 3] } else { 0 };
+                let predicted = match kind {
+                    0 => 0,
+                    1 => a,
+                    2 => b,
+                    3 => ((a as u16 + b as u16) / 2) as u8,
+                    _ => paeth(a, b, c),
+                };
+                recon[i] = filtered[i + 1].wrapping_add(predicted);
+            }
+            assert_eq!(recon, raw);
+        }
+    }
+}
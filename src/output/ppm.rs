@@ -0,0 +1,50 @@
+use std::io::{self, Write};
+
+use crate::{
+    color::{to_rgb_bytes, write_color},
+    image::Image,
+};
+
+// Plain-text PPM (P3), gamma corrected and quantized to bytes
+pub fn write_ppm<W: Write>(out: &mut W, image: &Image) -> io::Result<()> {
+    let header = format!("P3\n{} {}\n255\n", image.width(), image.height());
+    out.write_all(header.as_bytes())?;
+    for pixel_color in image.pixels() {
+        write_color(out, pixel_color)?;
+    }
+    Ok(())
+}
+
+// Binary PPM (P6), same quantization as P3 with raw bytes instead of text
+pub fn write_ppm_binary<W: Write>(out: &mut W, image: &Image) -> io::Result<()> {
+    let header = format!("P6\n{} {}\n255\n", image.width(), image.height());
+    out.write_all(header.as_bytes())?;
+    let bytes: Vec<u8> = image.pixels().iter().flat_map(to_rgb_bytes).collect();
+    out.write_all(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    #[test]
+    fn ppm_header_and_pixels() {
+        let mut image = Image::new(2, 1);
+        image.set(1, 0, Color::new(1.0, 0.25, 0.0));
+        let mut out = Vec::new();
+        write_ppm(&mut out, &image).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(text, "P3\n2 1\n255\n0 0 0\n255 128 0\n");
+    }
+
+    #[test]
+    fn p6_header_and_pixels() {
+        let mut image = Image::new(2, 1);
+        image.set(1, 0, Color::new(1.0, 0.25, 0.0));
+        let mut out = Vec::new();
+        write_ppm_binary(&mut out, &image).unwrap();
+        assert_eq!(&out[..11], b"P6\n2 1\n255\n");
+        assert_eq!(&out[11..], &[0, 0, 0, 255, 128, 0]);
+    }
+}
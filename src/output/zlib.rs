@@ -0,0 +1,519 @@
+// Minimal zlib (RFC 1950) stream encoder using fixed-Huffman deflate (RFC 1951)
+// with greedy LZ77 matching over a 32K window, and a decoder for all three
+// deflate block types
+
+const WINDOW_SIZE: usize = 1 << 15;
+const WINDOW_MASK: usize = WINDOW_SIZE - 1;
+const HASH_BITS: usize = 15;
+const HASH_SIZE: usize = 1 << HASH_BITS;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const MAX_CHAIN: usize = 64;
+const NIL: usize = usize::MAX;
+
+// Base values and extra bit counts for length codes 257..=285
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+// Base values and extra bit counts for distance codes 0..=29
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+struct BitWriter {
+    out: Vec<u8>,
+    acc: u32,
+    nbits: u32,
+}
+
+impl BitWriter {
+    fn new(out: Vec<u8>) -> Self {
+        BitWriter {
+            out,
+            acc: 0,
+            nbits: 0,
+        }
+    }
+
+    // Writes the low `n` bits of `bits`, least significant first
+    fn write_bits(&mut self, bits: u32, n: u32) {
+        self.acc |= bits << self.nbits;
+        self.nbits += n;
+        while self.nbits >= 8 {
+            self.out.push(self.acc as u8);
+            self.acc >>= 8;
+            self.nbits -= 8;
+        }
+    }
+
+    // Huffman codes are packed most significant bit first
+    fn write_code(&mut self, code: u32, len: u32) {
+        let reversed = code.reverse_bits() >> (32 - len);
+        self.write_bits(reversed, len);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.nbits > 0 {
+            self.out.push(self.acc as u8);
+        }
+        self.out
+    }
+}
+
+fn write_literal(w: &mut BitWriter, lit: u32) {
+    match lit {
+        0..=143 => w.write_code(0x30 + lit, 8),
+        144..=255 => w.write_code(0x190 + (lit - 144), 9),
+        256..=279 => w.write_code(lit - 256, 7),
+        _ => w.write_code(0xc0 + (lit - 280), 8),
+    }
+}
+
+fn write_match(w: &mut BitWriter, len: usize, dist: usize) {
+    let li = LENGTH_BASE
+        .iter()
+        .rposition(|&b| b as usize <= len)
+        .unwrap();
+    write_literal(w, 257 + li as u32);
+    w.write_bits(
+        (len - LENGTH_BASE[li] as usize) as u32,
+        LENGTH_EXTRA[li] as u32,
+    );
+
+    let di = DIST_BASE.iter().rposition(|&b| b as usize <= dist).unwrap();
+    w.write_code(di as u32, 5);
+    w.write_bits(
+        (dist - DIST_BASE[di] as usize) as u32,
+        DIST_EXTRA[di] as u32,
+    );
+}
+
+fn hash(data: &[u8], i: usize) -> usize {
+    let v = (data[i] as usize) << 16 | (data[i + 1] as usize) << 8 | data[i + 2] as usize;
+    (v.wrapping_mul(2654435761) >> 8) & (HASH_SIZE - 1)
+}
+
+fn insert(data: &[u8], head: &mut [usize], prev: &mut [usize], i: usize) {
+    if i + MIN_MATCH <= data.len() {
+        let h = hash(data, i);
+        prev[i & WINDOW_MASK] = head[h];
+        head[h] = i;
+    }
+}
+
+// Raw deflate stream as a single final fixed-Huffman block
+pub fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut w = BitWriter::new(Vec::new());
+    // BFINAL = 1, BTYPE = 01 (fixed Huffman)
+    w.write_bits(1, 1);
+    w.write_bits(1, 2);
+
+    let mut head = vec![NIL; HASH_SIZE];
+    let mut prev = vec![NIL; WINDOW_SIZE];
+
+    let mut i = 0;
+    while i < data.len() {
+        let mut best_len = 0;
+        let mut best_dist = 0;
+
+        if i + MIN_MATCH <= data.len() {
+            let max_len = usize::min(MAX_MATCH, data.len() - i);
+            let mut candidate = head[hash(data, i)];
+            let mut chain = 0;
+            while candidate != NIL && i - candidate <= WINDOW_SIZE && chain < MAX_CHAIN {
+                let len = data[candidate..]
+                    .iter()
+                    .zip(&data[i..i + max_len])
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                if len > best_len {
+                    best_len = len;
+                    best_dist = i - candidate;
+                    if len == max_len {
+                        break;
+                    }
+                }
+                let next = prev[candidate & WINDOW_MASK];
+                // Entries older than the window have been overwritten
+                if next == NIL || next >= candidate {
+                    break;
+                }
+                candidate = next;
+                chain += 1;
+            }
+        }
+
+        if best_len >= MIN_MATCH {
+            write_match(&mut w, best_len, best_dist);
+            for k in i..i + best_len {
+                insert(data, &mut head, &mut prev, k);
+            }
+            i += best_len;
+        } else {
+            write_literal(&mut w, data[i] as u32);
+            insert(data, &mut head, &mut prev, i);
+            i += 1;
+        }
+    }
+
+    write_literal(&mut w, 256);
+    w.finish()
+}
+
+pub fn adler32(data: &[u8]) -> u32 {
+    const MOD_ADLER: u32 = 65521;
+    let mut a: u32 = 1;
+    let mut b: u32 = 0;
+    // 5552 is the largest block that cannot overflow u32 before reducing
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MOD_ADLER;
+        b %= MOD_ADLER;
+    }
+    (b << 16) | a
+}
+
+// zlib container: header, deflate payload, big-endian Adler-32 trailer
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    out.extend(deflate(data));
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bit: u32,
+}
+
+impl BitReader<'_> {
+    // Reads `n` bits, least significant first
+    fn bits(&mut self, n: u32) -> Result<u32, &'static str> {
+        let mut value = 0;
+        for i in 0..n {
+            let byte = *self.data.get(self.pos).ok_or("unexpected end of data")?;
+            value |= ((byte >> self.bit) as u32 & 1) << i;
+            self.bit += 1;
+            if self.bit == 8 {
+                self.bit = 0;
+                self.pos += 1;
+            }
+        }
+        Ok(value)
+    }
+
+    fn align(&mut self) {
+        if self.bit > 0 {
+            self.bit = 0;
+            self.pos += 1;
+        }
+    }
+}
+
+// Canonical Huffman code, decoded a bit at a time from the code lengths
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Self, &'static str> {
+        let mut counts = [0u16; 16];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        // Incomplete codes are allowed, over-subscribed ones are not
+        let mut left: i32 = 1;
+        for &count in &counts[1..] {
+            left = 2 * left - count as i32;
+            if left < 0 {
+                return Err("over-subscribed Huffman code");
+            }
+        }
+
+        let mut offsets = [0u16; 16];
+        for len in 1..15 {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize] as usize] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+        Ok(Huffman { counts, symbols })
+    }
+
+    fn decode(&self, r: &mut BitReader) -> Result<u16, &'static str> {
+        // Codes of each length follow on from the shorter ones
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for &count in &self.counts[1..] {
+            code |= r.bits(1)? as i32;
+            let count = count as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err("invalid Huffman code")
+    }
+}
+
+// Order the code length code lengths of a dynamic block are stored in
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+fn fixed_codes() -> (Huffman, Huffman) {
+    let mut lengths = [8u8; 288];
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    (
+        Huffman::new(&lengths).unwrap(),
+        Huffman::new(&[5; 30]).unwrap(),
+    )
+}
+
+fn dynamic_codes(r: &mut BitReader) -> Result<(Huffman, Huffman), &'static str> {
+    let nlen = r.bits(5)? as usize + 257;
+    let ndist = r.bits(5)? as usize + 1;
+    let ncode = r.bits(4)? as usize + 4;
+    if nlen > 286 || ndist > 30 {
+        return Err("too many length or distance codes");
+    }
+
+    let mut code_lengths = [0u8; 19];
+    for &i in &CODE_LENGTH_ORDER[..ncode] {
+        code_lengths[i] = r.bits(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&code_lengths)?;
+
+    let mut lengths = vec![0u8; nlen + ndist];
+    let mut i = 0;
+    while i < lengths.len() {
+        let symbol = code_lengths.decode(r)?;
+        if symbol < 16 {
+            lengths[i] = symbol as u8;
+            i += 1;
+            continue;
+        }
+        let (value, repeat) = match symbol {
+            16 if i == 0 => return Err("repeat with no previous length"),
+            16 => (lengths[i - 1], 3 + r.bits(2)?),
+            17 => (0, 3 + r.bits(3)?),
+            _ => (0, 11 + r.bits(7)?),
+        };
+        let end = i + repeat as usize;
+        if end > lengths.len() {
+            return Err("code lengths overrun");
+        }
+        lengths[i..end].fill(value);
+        i = end;
+    }
+    if lengths[256] == 0 {
+        return Err("missing end of block code");
+    }
+    Ok((
+        Huffman::new(&lengths[..nlen])?,
+        Huffman::new(&lengths[nlen..])?,
+    ))
+}
+
+fn inflate_codes(
+    r: &mut BitReader,
+    out: &mut Vec<u8>,
+    lit: &Huffman,
+    dist: &Huffman,
+) -> Result<(), &'static str> {
+    loop {
+        let symbol = lit.decode(r)? as usize;
+        if symbol < 256 {
+            out.push(symbol as u8);
+            continue;
+        }
+        if symbol == 256 {
+            return Ok(());
+        }
+        let li = symbol - 257;
+        if li >= LENGTH_BASE.len() {
+            return Err("invalid length code");
+        }
+        let len = LENGTH_BASE[li] as usize + r.bits(LENGTH_EXTRA[li] as u32)? as usize;
+        let di = dist.decode(r)? as usize;
+        if di >= DIST_BASE.len() {
+            return Err("invalid distance code");
+        }
+        let d = DIST_BASE[di] as usize + r.bits(DIST_EXTRA[di] as u32)? as usize;
+        if d > out.len() {
+            return Err("distance too far back");
+        }
+        // Byte by byte, as a match may overlap the bytes it produces
+        let start = out.len() - d;
+        for k in 0..len {
+            out.push(out[start + k]);
+        }
+    }
+}
+
+// Decodes a raw deflate stream, returning the data and the number of input
+// bytes it took up
+fn inflate_stream(data: &[u8]) -> Result<(Vec<u8>, usize), &'static str> {
+    let mut r = BitReader {
+        data,
+        pos: 0,
+        bit: 0,
+    };
+    let mut out = Vec::new();
+    loop {
+        let last = r.bits(1)? == 1;
+        match r.bits(2)? {
+            0 => {
+                r.align();
+                let header = data.get(r.pos..r.pos + 4).ok_or("unexpected end of data")?;
+                let len = u16::from_le_bytes([header[0], header[1]]);
+                let nlen = u16::from_le_bytes([header[2], header[3]]);
+                if len != !nlen {
+                    return Err("stored block length mismatch");
+                }
+                let start = r.pos + 4;
+                let stored = data
+                    .get(start..start + len as usize)
+                    .ok_or("unexpected end of data")?;
+                out.extend_from_slice(stored);
+                r.pos = start + len as usize;
+            }
+            1 => {
+                let (lit, dist) = fixed_codes();
+                inflate_codes(&mut r, &mut out, &lit, &dist)?;
+            }
+            2 => {
+                let (lit, dist) = dynamic_codes(&mut r)?;
+                inflate_codes(&mut r, &mut out, &lit, &dist)?;
+            }
+            _ => return Err("invalid block type"),
+        }
+        if last {
+            r.align();
+            return Ok((out, r.pos));
+        }
+    }
+}
+
+pub fn inflate(data: &[u8]) -> Result<Vec<u8>, &'static str> {
+    inflate_stream(data).map(|(out, _)| out)
+}
+
+// Unwraps a zlib container, checking its header and Adler-32 trailer
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let [cmf, flg, ..] = *data else {
+        return Err("truncated zlib header");
+    };
+    if cmf & 0x0f != 8 || !((cmf as u16) << 8 | flg as u16).is_multiple_of(31) {
+        return Err("invalid zlib header");
+    }
+    if flg & 0x20 != 0 {
+        return Err("preset dictionaries are not supported");
+    }
+    let (out, used) = inflate_stream(&data[2..])?;
+    let trailer = data
+        .get(2 + used..2 + used + 4)
+        .ok_or("missing Adler-32 checksum")?;
+    if trailer != adler32(&out).to_be_bytes() {
+        return Err("Adler-32 checksum mismatch");
+    }
+    Ok(out)
+}
+
+// CRC-32 (IEEE 802.3) as used by PNG chunks
+pub fn crc32(data: &[u8]) -> u32 {
+    crc32_update(0xffff_ffff, data) ^ 0xffff_ffff
+}
+
+pub fn crc32_update(mut crc: u32, data: &[u8]) -> u32 {
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksums() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+        assert_eq!(adler32(b""), 1);
+    }
+
+    #[test]
+    fn zlib_header_is_valid() {
+        let out = compress(b"hello hello hello hello");
+        assert_eq!(((out[0] as u16) << 8 | out[1] as u16) % 31, 0);
+        let trailer = &out[out.len() - 4..];
+        assert_eq!(trailer, adler32(b"hello hello hello hello").to_be_bytes());
+    }
+
+    #[test]
+    fn inflates_own_output() {
+        let data: Vec<u8> = (0..20_000u32).map(|i| (i * i % 251 / 7) as u8).collect();
+        assert_eq!(decompress(&compress(&data)).unwrap(), data);
+        assert_eq!(decompress(&compress(b"")).unwrap(), b"");
+    }
+
+    #[test]
+    fn inflates_stored_and_dynamic_blocks() {
+        // A stored block holding "abc"
+        let stored = [0x01, 3, 0, 0xfc, 0xff, b'a', b'b', b'c'];
+        assert_eq!(inflate(&stored).unwrap(), b"abc");
+        assert!(inflate(&stored[..7]).is_err());
+
+        // zlib level 9 output, which uses a dynamic Huffman block
+        let hex =
+            "78dab5cbd10180101446e155fe1668961e2c401115378498bebb44cfe73bc26ac4e2d6132a510b30\
+                   f4e228fecea0aa131ece971c1d1bed33c46f7891ec7c8762d4dc63615cd59c860eb85c2c94f8ddf3\
+                   f401b2ee3f00";
+        let bytes: Vec<u8> = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect();
+        let mut expected = b"The quick brown fox jumps over the lazy dog. ".repeat(3);
+        expected.extend(b"Pack my box with five dozen liquor jugs!");
+        assert_eq!(decompress(&bytes).unwrap(), expected);
+
+        let mut corrupt = bytes.clone();
+        *corrupt.last_mut().unwrap() ^= 1;
+        assert_eq!(decompress(&corrupt), Err("Adler-32 checksum mismatch"));
+    }
+
+    #[test]
+    fn repetitive_data_compresses() {
+        let data = vec![7u8; 100_000];
+        assert!(compress(&data).len() < 1_000);
+    }
+}
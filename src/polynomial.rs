@@ -0,0 +1,181 @@
+// Real roots of low degree polynomials, highest degree coefficient first.
+// Roots come back in ascending order.
+
+// Newton steps on a root found in closed form, to win back the precision
+// lost to cancellation
+fn polish(coeffs: &[f64], mut x: f64) -> f64 {
+    for _ in 0..4 {
+        let (mut value, mut slope) = (0.0, 0.0);
+        for &c in coeffs {
+            slope = slope * x + value;
+            value = value * x + c;
+        }
+        if slope == 0.0 {
+            break;
+        }
+        let step = value / slope;
+        if !step.is_finite() {
+            break;
+        }
+        x -= step;
+        if step.abs() <= 1e-15 * x.abs() {
+            break;
+        }
+    }
+    x
+}
+
+// a x^2 + b x + c = 0, falling back to the linear equation when a is zero
+pub fn solve_quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if a == 0.0 {
+        return if b == 0.0 { Vec::new() } else { vec![-c / b] };
+    }
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return Vec::new();
+    }
+    // Avoids subtracting nearly equal values
+    let q = -0.5 * (b + f64::copysign(discriminant.sqrt(), b));
+    if q == 0.0 {
+        return vec![0.0, 0.0];
+    }
+    let (x0, x1) = (q / a, c / q);
+    if x0 < x1 {
+        vec![x0, x1]
+    } else {
+        vec![x1, x0]
+    }
+}
+
+// a x^3 + b x^2 + c x + d = 0
+pub fn solve_cubic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    if a == 0.0 {
+        return solve_quadratic(b, c, d);
+    }
+    let (b, c, d) = (b / a, c / a, d / a);
+
+    // Depressed cubic t^3 + p t + q with x = t - b / 3
+    let shift = b / 3.0;
+    let p = c - b * shift;
+    let q = 2.0 * shift * shift * shift - shift * c + d;
+    let discriminant = q * q / 4.0 + p * p * p / 27.0;
+
+    let mut roots = if discriminant >= 0.0 {
+        let s = discriminant.sqrt();
+        vec![f64::cbrt(-q / 2.0 + s) + f64::cbrt(-q / 2.0 - s)]
+    } else {
+        // Three real roots, from the trigonometric form
+        let r = 2.0 * f64::sqrt(-p / 3.0);
+        let phi = f64::acos((3.0 * q / (p * r)).clamp(-1.0, 1.0)) / 3.0;
+        (0..3)
+            .map(|k| r * f64::cos(phi - 2.0 * std::f64::consts::PI * k as f64 / 3.0))
+            .collect()
+    };
+
+    let coeffs = [1.0, b, c, d];
+    for root in roots.iter_mut() {
+        *root = polish(&coeffs, *root - shift);
+    }
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+// a x^4 + b x^3 + c x^2 + d x + e = 0 by Ferrari's method
+pub fn solve_quartic(a: f64, b: f64, c: f64, d: f64, e: f64) -> Vec<f64> {
+    if a == 0.0 {
+        return solve_cubic(b, c, d, e);
+    }
+    let (b, c, d, e) = (b / a, c / a, d / a, e / a);
+
+    // Depressed quartic y^4 + p y^2 + q y + r with x = y - b / 4
+    let shift = b / 4.0;
+    let shift2 = shift * shift;
+    let p = c - 6.0 * shift2;
+    let q = d - 2.0 * c * shift + 8.0 * shift2 * shift;
+    let r = e - d * shift + c * shift2 - 3.0 * shift2 * shift2;
+
+    let mut roots = Vec::with_capacity(4);
+    if q.abs() < 1e-12 {
+        // Biquadratic in y^2
+        for z in solve_quadratic(1.0, p, r) {
+            if z >= 0.0 {
+                roots.push(z.sqrt());
+                roots.push(-z.sqrt());
+            }
+        }
+    } else {
+        // Any positive root m of the resolvent cubic splits the quartic into
+        // (y^2 + p/2 + m)^2 = (s y - q / 2s)^2 with s = sqrt(2m). It has one,
+        // as the cubic is negative at zero.
+        let m = solve_cubic(8.0, 8.0 * p, 2.0 * p * p - 8.0 * r, -q * q)
+            .into_iter()
+            .fold(0.0, f64::max);
+        if m > 0.0 {
+            let s = f64::sqrt(2.0 * m);
+            let base = p / 2.0 + m;
+            let offset = q / (2.0 * s);
+            roots.extend(solve_quadratic(1.0, -s, base + offset));
+            roots.extend(solve_quadratic(1.0, s, base - offset));
+        }
+    }
+
+    let coeffs = [1.0, b, c, d, e];
+    for root in roots.iter_mut() {
+        *root = polish(&coeffs, *root - shift);
+    }
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_roots(found: Vec<f64>, expected: &[f64]) {
+        assert_eq!(found.len(), expected.len(), "roots {:?}", found);
+        for (x, y) in found.iter().zip(expected) {
+            assert!(
+                (x - y).abs() < 1e-9,
+                "roots {:?}, expected {:?}",
+                found,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn quadratic_and_cubic() {
+        assert_roots(solve_quadratic(1.0, -3.0, 2.0), &[1.0, 2.0]);
+        assert_roots(solve_quadratic(1.0, 0.0, 1.0), &[]);
+        assert_roots(solve_quadratic(0.0, 2.0, -4.0), &[2.0]);
+        // Cancellation prone: roots 1e-8 and 1e8
+        assert_roots(solve_quadratic(1.0, -1e8 - 1e-8, 1.0), &[1e-8, 1e8]);
+
+        assert_roots(solve_cubic(1.0, -6.0, 11.0, -6.0), &[1.0, 2.0, 3.0]);
+        assert_roots(solve_cubic(2.0, 0.0, 0.0, -16.0), &[2.0]);
+    }
+
+    #[test]
+    fn quartic() {
+        // (x - 1)(x - 2)(x - 3)(x - 4)
+        assert_roots(
+            solve_quartic(1.0, -10.0, 35.0, -50.0, 24.0),
+            &[1.0, 2.0, 3.0, 4.0],
+        );
+        // Biquadratic (x^2 - 1)(x^2 - 4)
+        assert_roots(
+            solve_quartic(1.0, 0.0, -5.0, 0.0, 4.0),
+            &[-2.0, -1.0, 1.0, 2.0],
+        );
+        // (x^2 + 1)(x - 0.5)(x + 3): two complex roots
+        assert_roots(solve_quartic(1.0, 2.5, -0.5, 2.5, -1.5), &[-3.0, 0.5]);
+        assert_roots(solve_quartic(1.0, 0.0, 2.0, 0.0, 1.0), &[]);
+        // Widely spread roots 0.001, 1, 10 and 1000
+        let r = [0.001, 1.0, 10.0, 1000.0];
+        let e1 = r.iter().sum::<f64>();
+        let e2 = r[0] * r[1] + r[0] * r[2] + r[0] * r[3] + r[1] * r[2] + r[1] * r[3] + r[2] * r[3];
+        let e3 = r[0] * r[1] * r[2] + r[0] * r[1] * r[3] + r[0] * r[2] * r[3] + r[1] * r[2] * r[3];
+        let e4 = r.iter().product::<f64>();
+        assert_roots(solve_quartic(1.0, -e1, e2, -e3, e4), &r);
+    }
+}
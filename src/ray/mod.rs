@@ -3,11 +3,17 @@ use crate::vec3::{Point3, Vec3};
 pub struct Ray {
     origin: Point3,
     dir: Vec3,
+    // Moment within the camera shutter the ray is cast at
+    time: f64,
 }
 
 impl Ray {
     pub fn new(origin: Point3, dir: Vec3) -> Self {
-        Ray { origin, dir }
+        Ray::with_time(origin, dir, 0.0)
+    }
+
+    pub fn with_time(origin: Point3, dir: Vec3, time: f64) -> Self {
+        Ray { origin, dir, time }
     }
 
     pub fn origin(&self) -> &Point3 {
@@ -18,6 +24,10 @@ impl Ray {
         &self.dir
     }
 
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
     pub fn at(&self, t: f64) -> Point3 {
         self.origin + t * self.dir
     }
@@ -0,0 +1,1162 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{
+    background::{Background, EnvironmentMap},
+    camera::CameraSettings,
+    color::Color,
+    hittable::Hittable,
+    loaders::{load_image, load_image_raw, load_model},
+    material::{normal_map::NormalDetail, MaterialType},
+    objects::{
+        cone::Cone, constant_medium::ConstantMedium, cuboid::Cuboid, cylinder::Cylinder,
+        disk::Disk, hittable_list::HittableList, moving::Moving, plane::Plane, quad::Quad,
+        sphere::Sphere, torus::Torus, transformed::Transformed, triangle::Triangle,
+    },
+    texture::{
+        image_texture::{Filter, Wrap},
+        noise::NoisePattern,
+        TextureType,
+    },
+    vec3::{Mat4, Vec3},
+};
+
+// Line-oriented scene description. Each non-empty line is a directive
+// followed by whitespace separated key=value pairs, '#' starts a comment:
+//
+//   camera image_width=400 vfov=20 look_from=13,2,3 look_at=0,0,0
+//   texture <name> <type> key=value ...
+//   material <name> <type> key=value ...
+//   background <type> key=value ...
+//   <object> key=value ... material=<name>
+//
+// README.md lists every directive and key.
+pub struct Scene {
+    pub camera: CameraSettings,
+    pub world: HittableList,
+    pub background: Background,
+}
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io(io::Error),
+    Parse { line: usize, message: String },
+}
+
+impl Display for SceneError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Io(e) => write!(f, "{}", e),
+            SceneError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+impl From<io::Error> for SceneError {
+    fn from(e: io::Error) -> Self {
+        SceneError::Io(e)
+    }
+}
+
+// Key=value arguments of a single directive
+struct Params<'a> {
+    line: usize,
+    values: HashMap<&'a str, &'a str>,
+}
+
+impl<'a> Params<'a> {
+    fn parse(line: usize, tokens: &[&'a str]) -> Result<Self, SceneError> {
+        let mut values = HashMap::new();
+        for token in tokens {
+            let (key, value) = token
+                .split_once('=')
+                .ok_or_else(|| error(line, format!("expected key=value, found '{}'", token)))?;
+            if values.insert(key, value).is_some() {
+                return Err(error(line, format!("duplicate key '{}'", key)));
+            }
+        }
+        Ok(Params { line, values })
+    }
+
+    fn str(&mut self, key: &str) -> Result<&'a str, SceneError> {
+        self.values
+            .remove(key)
+            .ok_or_else(|| error(self.line, format!("missing '{}'", key)))
+    }
+
+    fn opt_f64(&mut self, key: &str) -> Result<Option<f64>, SceneError> {
+        match self.values.remove(key) {
+            Some(value) => parse_f64(self.line, key, value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn f64(&mut self, key: &str) -> Result<f64, SceneError> {
+        let value = self.str(key)?;
+        parse_f64(self.line, key, value)
+    }
+
+    fn opt_i32(&mut self, key: &str) -> Result<Option<i32>, SceneError> {
+        match self.values.remove(key) {
+            Some(value) => match value.parse::<i32>() {
+                Ok(v) if v > 0 => Ok(Some(v)),
+                _ => Err(error(
+                    self.line,
+                    format!("'{}' must be a positive integer, found '{}'", key, value),
+                )),
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn opt_u64(&mut self, key: &str) -> Result<Option<u64>, SceneError> {
+        match self.values.remove(key) {
+            Some(value) => value.parse::<u64>().map(Some).map_err(|_| {
+                error(
+                    self.line,
+                    format!(
+                        "'{}' must be a non-negative integer, found '{}'",
+                        key, value
+                    ),
+                )
+            }),
+            None => Ok(None),
+        }
+    }
+
+    fn opt_bool(&mut self, key: &str) -> Result<Option<bool>, SceneError> {
+        match self.values.remove(key) {
+            Some("true") => Ok(Some(true)),
+            Some("false") => Ok(Some(false)),
+            Some(value) => Err(error(
+                self.line,
+                format!("'{}' expects true or false, found '{}'", key, value),
+            )),
+            None => Ok(None),
+        }
+    }
+
+    fn opt_vec3(&mut self, key: &str) -> Result<Option<Vec3>, SceneError> {
+        match self.values.remove(key) {
+            Some(value) => parse_vec3(self.line, key, value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn vec3(&mut self, key: &str) -> Result<Vec3, SceneError> {
+        let value = self.str(key)?;
+        parse_vec3(self.line, key, value)
+    }
+
+    // Rejects any keys the directive did not consume
+    fn finish(self) -> Result<(), SceneError> {
+        let mut unknown: Vec<_> = self.values.keys().collect();
+        unknown.sort();
+        match unknown.first() {
+            Some(key) => Err(error(self.line, format!("unknown key '{}'", key))),
+            None => Ok(()),
+        }
+    }
+}
+
+fn error(line: usize, message: String) -> SceneError {
+    SceneError::Parse { line, message }
+}
+
+fn parse_f64(line: usize, key: &str, value: &str) -> Result<f64, SceneError> {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|v| v.is_finite())
+        .ok_or_else(|| {
+            error(
+                line,
+                format!("'{}' expects a number, found '{}'", key, value),
+            )
+        })
+}
+
+fn parse_vec3(line: usize, key: &str, value: &str) -> Result<Vec3, SceneError> {
+    let parts: Vec<&str> = value.split(',').collect();
+    if parts.len() != 3 {
+        return Err(error(
+            line,
+            format!(
+                "'{}' expects three comma separated numbers, found '{}'",
+                key, value
+            ),
+        ));
+    }
+    Ok(Vec3::new(
+        parse_f64(line, key, parts[0])?,
+        parse_f64(line, key, parts[1])?,
+        parse_f64(line, key, parts[2])?,
+    ))
+}
+
+fn parse_camera(params: &mut Params, camera: &mut CameraSettings) -> Result<(), SceneError> {
+    if let Some(v) = params.opt_f64("aspect_ratio")? {
+        camera.aspect_ratio = v;
+    }
+    if let Some(v) = params.opt_i32("image_width")? {
+        camera.image_width = v;
+    }
+    if let Some(v) = params.opt_i32("samples_per_pixel")? {
+        camera.samples_per_pixel = v;
+    }
+    if let Some(v) = params.opt_i32("recursion_depth")? {
+        camera.recursion_depth = v as usize;
+    }
+    if let Some(v) = params.opt_f64("vfov")? {
+        camera.vfov = v;
+    }
+    if let Some(v) = params.opt_vec3("look_from")? {
+        camera.look_from = v;
+    }
+    if let Some(v) = params.opt_vec3("look_at")? {
+        camera.look_at = v;
+    }
+    if let Some(v) = params.opt_vec3("vup")? {
+        camera.vup = v;
+    }
+    if let Some(v) = params.opt_f64("focus_dist")? {
+        camera.focus_dist = v;
+    }
+    if let Some(v) = params.opt_f64("defocus_angle")? {
+        camera.defocus_angle = v;
+    }
+    if let Some(v) = params.opt_f64("shutter_open")? {
+        camera.shutter_open = v;
+    }
+    if let Some(v) = params.opt_f64("shutter_close")? {
+        camera.shutter_close = v;
+    }
+    Ok(())
+}
+
+fn lookup_texture(
+    textures: &HashMap<&str, TextureType>,
+    line: usize,
+    name: &str,
+) -> Result<TextureType, SceneError> {
+    textures
+        .get(name)
+        .cloned()
+        .ok_or_else(|| error(line, format!("undefined texture '{}'", name)))
+}
+
+// A colour written out in place, or the name of a texture
+fn texture_or_color(
+    params: &mut Params,
+    key: &str,
+    textures: &HashMap<&str, TextureType>,
+) -> Result<TextureType, SceneError> {
+    let value = params.str(key)?;
+    if value.contains(',') {
+        Ok(TextureType::solid(parse_vec3(params.line, key, value)?))
+    } else {
+        lookup_texture(textures, params.line, value)
+    }
+}
+
+// Either `texture=<name>` or a plain `albedo=r,g,b`
+fn parse_albedo(
+    params: &mut Params,
+    textures: &HashMap<&str, TextureType>,
+) -> Result<TextureType, SceneError> {
+    if params.values.contains_key("texture") {
+        lookup_texture(textures, params.line, params.str("texture")?)
+    } else {
+        Ok(TextureType::solid(params.vec3("albedo")?))
+    }
+}
+
+fn parse_texture(
+    line: usize,
+    kind: &str,
+    params: &mut Params,
+    textures: &HashMap<&str, TextureType>,
+    base: &Path,
+) -> Result<TextureType, SceneError> {
+    match kind {
+        "solid" => Ok(TextureType::solid(params.vec3("color")?)),
+        "checker" => {
+            let scale = params.f64("scale")?;
+            if scale <= 0.0 {
+                return Err(error(line, "'scale' must be positive".into()));
+            }
+            let even = texture_or_color(params, "even", textures)?;
+            let odd = texture_or_color(params, "odd", textures)?;
+            Ok(TextureType::checker(scale, even, odd))
+        }
+        "uv_checker" => {
+            // Counts below one are rejected by opt_i32, so the casts are lossless
+            let width = params.opt_i32("width")?.unwrap_or(8) as u32;
+            let height = params.opt_i32("height")?.unwrap_or(8) as u32;
+            let even = texture_or_color(params, "even", textures)?;
+            let odd = texture_or_color(params, "odd", textures)?;
+            Ok(TextureType::uv_checker(width, height, even, odd))
+        }
+        // Blends from `low` to `high`, black to white by default
+        "perlin" | "turbulence" | "marble" | "wood" | "worley" => {
+            let scale = params.opt_f64("scale")?.unwrap_or(1.0);
+            let seed = params.opt_u64("seed")?.unwrap_or(0);
+            let low = params.opt_vec3("low")?.unwrap_or(Color::new(0.0, 0.0, 0.0));
+            let high = params
+                .opt_vec3("high")?
+                .unwrap_or(Color::new(1.0, 1.0, 1.0));
+            let pattern = match kind {
+                "perlin" => NoisePattern::Perlin,
+                "turbulence" => NoisePattern::Turbulence,
+                "marble" => NoisePattern::Marble,
+                "wood" => NoisePattern::Wood,
+                _ => return Ok(TextureType::worley(scale, seed, low, high)),
+            };
+            Ok(TextureType::noise(pattern, scale, seed, low, high))
+        }
+        // PNG, PPM, HDR or PFM, bilinear and repeating by default
+        "image" => {
+            let path = base.join(params.str("path")?);
+            let filter = match params.values.remove("filter") {
+                None | Some("bilinear") => Filter::Bilinear,
+                Some("nearest") => Filter::Nearest,
+                Some(value) => {
+                    return Err(error(
+                        line,
+                        format!("'filter' expects nearest or bilinear, found '{}'", value),
+                    ))
+                }
+            };
+            let wrap = match params.values.remove("wrap") {
+                None | Some("repeat") => Wrap::Repeat,
+                Some("clamp") => Wrap::Clamp,
+                Some("mirror") => Wrap::Mirror,
+                Some(value) => {
+                    return Err(error(
+                        line,
+                        format!("'wrap' expects repeat, clamp or mirror, found '{}'", value),
+                    ))
+                }
+            };
+            // Colour images are gamma encoded, data such as heights is not
+            let image = if params.opt_bool("srgb")?.unwrap_or(true) {
+                load_image(&path)
+            } else {
+                load_image_raw(&path)
+            };
+            let image = image.map_err(|e| error(line, e.to_string()))?;
+            Ok(TextureType::image(Arc::new(image), filter, wrap))
+        }
+        _ => Err(error(line, format!("unknown texture type '{}'", kind))),
+    }
+}
+
+fn parse_material(
+    line: usize,
+    kind: &str,
+    params: &mut Params,
+    textures: &HashMap<&str, TextureType>,
+) -> Result<MaterialType, SceneError> {
+    let mat = match kind {
+        "lambertian" => Ok(MaterialType::lambertian_texture(parse_albedo(
+            params, textures,
+        )?)),
+        "metal" => {
+            let albedo = parse_albedo(params, textures)?;
+            let fuzz = params.opt_f64("fuzz")?.unwrap_or(0.0);
+            Ok(MaterialType::metal_texture(albedo, fuzz))
+        }
+        "dielectric" => Ok(MaterialType::dielectric(params.f64("refraction_index")?)),
+        "isotropic" => Ok(MaterialType::isotropic_texture(parse_albedo(
+            params, textures,
+        )?)),
+        "light" => {
+            let emit: Color = params.vec3("emit")?;
+            let intensity = params.opt_f64("intensity")?.unwrap_or(1.0);
+            Ok(MaterialType::diffuse_light(intensity * emit))
+        }
+        _ => Err(error(line, format!("unknown material type '{}'", kind))),
+    }?;
+    parse_normal_detail(line, mat, params, textures)
+}
+
+// Wraps a material in the normal or bump map its keys ask for. The
+// strength and scale keys are only consumed alongside their map, so a
+// stray one is reported as unknown.
+fn parse_normal_detail(
+    line: usize,
+    mat: MaterialType,
+    params: &mut Params,
+    textures: &HashMap<&str, TextureType>,
+) -> Result<MaterialType, SceneError> {
+    let detail = match (
+        params.values.remove("normal_map"),
+        params.values.remove("bump"),
+    ) {
+        (Some(_), Some(_)) => {
+            return Err(error(line, "'normal_map' and 'bump' are exclusive".into()))
+        }
+        (Some(name), None) => NormalDetail::Map {
+            texture: lookup_texture(textures, line, name)?,
+            strength: params.opt_f64("normal_strength")?.unwrap_or(1.0),
+        },
+        (None, Some(name)) => NormalDetail::Bump {
+            height: lookup_texture(textures, line, name)?,
+            scale: params.opt_f64("bump_scale")?.unwrap_or(1.0),
+        },
+        (None, None) => return Ok(mat),
+    };
+    Ok(MaterialType::normal_mapped(mat, detail))
+}
+
+// What rays that miss everything see, the gradient sky unless set
+fn parse_background(
+    line: usize,
+    kind: &str,
+    params: &mut Params,
+    base: &Path,
+) -> Result<Background, SceneError> {
+    match kind {
+        "solid" => Ok(Background::Solid(params.vec3("color")?)),
+        "gradient" => Ok(Background::Gradient {
+            bottom: params.vec3("bottom")?,
+            top: params.vec3("top")?,
+        }),
+        // Equirectangular, turned `rotation` degrees about y
+        "environment" => {
+            let path = base.join(params.str("path")?);
+            let rotation = params.opt_f64("rotation")?.unwrap_or(0.0);
+            let intensity = params.opt_f64("intensity")?.unwrap_or(1.0);
+            let image = load_image(&path).map_err(|e| error(line, e.to_string()))?;
+            Ok(Background::Environment(EnvironmentMap::new(
+                image, rotation, intensity,
+            )))
+        }
+        _ => Err(error(line, format!("unknown background type '{}'", kind))),
+    }
+}
+
+fn lookup(
+    materials: &HashMap<&str, MaterialType>,
+    line: usize,
+    name: &str,
+) -> Result<MaterialType, SceneError> {
+    materials
+        .get(name)
+        .cloned()
+        .ok_or_else(|| error(line, format!("undefined material '{}'", name)))
+}
+
+// Builds a primitive from its directive, consuming its keys
+fn parse_shape(
+    line: usize,
+    directive: &str,
+    params: &mut Params,
+    materials: &HashMap<&str, MaterialType>,
+) -> Result<Box<dyn Hittable>, SceneError> {
+    let mat = |params: &mut Params| lookup(materials, line, params.str("material")?);
+    let shape: Box<dyn Hittable> = match directive {
+        "sphere" => {
+            let center = params.vec3("center")?;
+            let radius = params.f64("radius")?;
+            let sphere = Sphere::new(center, radius, mat(params)?);
+            // Moves to `center_end` over the shutter, like `motion`
+            match params.opt_vec3("center_end")? {
+                Some(end) => Box::new(Moving::new(sphere, end - center)),
+                None => Box::new(sphere),
+            }
+        }
+        "triangle" => {
+            let v0 = params.vec3("v0")?;
+            let v1 = params.vec3("v1")?;
+            let v2 = params.vec3("v2")?;
+            Box::new(Triangle::new(v0, v1, v2, mat(params)?))
+        }
+        "plane" => {
+            let point = params.vec3("point")?;
+            let normal = params.vec3("normal")?;
+            Box::new(Plane::new(point, normal, mat(params)?))
+        }
+        "quad" => {
+            let corner = params.vec3("corner")?;
+            let u = params.vec3("u")?;
+            let v = params.vec3("v")?;
+            Box::new(Quad::new(corner, u, v, mat(params)?))
+        }
+        "disk" => {
+            let center = params.vec3("center")?;
+            let normal = params.vec3("normal")?;
+            let radius = params.f64("radius")?;
+            Box::new(Disk::new(center, normal, radius, mat(params)?))
+        }
+        "box" => {
+            let min = params.vec3("min")?;
+            let max = params.vec3("max")?;
+            Box::new(Cuboid::new(min, max, mat(params)?))
+        }
+        // Upright on their base, capped unless `capped=false`
+        "cylinder" | "cone" => {
+            let base = params.vec3("base")?;
+            let radius = params.f64("radius")?;
+            let height = params.f64("height")?;
+            // Their normals and UVs divide by both
+            for (key, value) in [("radius", radius), ("height", height)] {
+                if value <= 0.0 {
+                    return Err(error(line, format!("'{}' must be positive", key)));
+                }
+            }
+            let capped = params.opt_bool("capped")?.unwrap_or(true);
+            if directive == "cylinder" {
+                Box::new(Cylinder::new(base, radius, height, capped, mat(params)?))
+            } else {
+                Box::new(Cone::new(base, radius, height, capped, mat(params)?))
+            }
+        }
+        "torus" => {
+            let center = params.vec3("center")?;
+            let major_radius = params.f64("major_radius")?;
+            let minor_radius = params.f64("minor_radius")?;
+            Box::new(Torus::new(center, major_radius, minor_radius, mat(params)?))
+        }
+        _ => return Err(error(line, format!("unknown directive '{}'", directive))),
+    };
+    Ok(shape)
+}
+
+// Optional placement shared by all objects: scale, then rotate about x, y
+// and z in degrees, then translate
+fn parse_transform(params: &mut Params) -> Result<Option<Mat4>, SceneError> {
+    let scale = match params.values.get("scale") {
+        Some(value) if !value.contains(',') => params.opt_f64("scale")?.map(|s| Vec3::new(s, s, s)),
+        _ => params.opt_vec3("scale")?,
+    };
+    let rotate = params.opt_vec3("rotate")?;
+    let translate = params.opt_vec3("translate")?;
+    if scale.is_none() && rotate.is_none() && translate.is_none() {
+        return Ok(None);
+    }
+
+    let mut m = Mat4::IDENTITY;
+    if let Some(scale) = scale {
+        m = Mat4::scaling(scale);
+    }
+    if let Some(rotate) = rotate {
+        m = Mat4::rotation_z(rotate.z())
+            * Mat4::rotation_y(rotate.y())
+            * Mat4::rotation_x(rotate.x())
+            * m;
+    }
+    if let Some(translate) = translate {
+        m = Mat4::translation(translate) * m;
+    }
+    if m.inverse().is_none() {
+        return Err(error(
+            params.line,
+            "transform is not invertible".to_string(),
+        ));
+    }
+    Ok(Some(m))
+}
+
+impl Scene {
+    pub fn load(path: &Path) -> Result<Scene, SceneError> {
+        let base = path.parent().unwrap_or(Path::new(""));
+        Scene::parse_in(&fs::read_to_string(path)?, base)
+    }
+
+    pub fn parse(source: &str) -> Result<Scene, SceneError> {
+        Scene::parse_in(source, Path::new(""))
+    }
+
+    // Parses a scene whose relative paths are resolved against `base`
+    pub fn parse_in(source: &str, base: &Path) -> Result<Scene, SceneError> {
+        let mut camera = CameraSettings::default();
+        let mut world = HittableList::new();
+        let mut background = Background::default();
+        let mut materials: HashMap<&str, MaterialType> = HashMap::new();
+        let mut textures: HashMap<&str, TextureType> = HashMap::new();
+        let mut meshes: HashMap<(PathBuf, Option<&str>), Arc<HittableList>> = HashMap::new();
+
+        for (index, raw_line) in source.lines().enumerate() {
+            let line = index + 1;
+            let content = raw_line.split('#').next().unwrap_or("");
+            let tokens: Vec<&str> = content.split_whitespace().collect();
+            let Some((&directive, args)) = tokens.split_first() else {
+                continue;
+            };
+
+            match directive {
+                "camera" => {
+                    let mut params = Params::parse(line, args)?;
+                    parse_camera(&mut params, &mut camera)?;
+                    params.finish()?;
+                }
+                "material" => {
+                    let (name, kind, rest) = match args {
+                        [name, kind, rest @ ..] => (*name, *kind, rest),
+                        _ => return Err(error(line, "expected 'material <name> <type>'".into())),
+                    };
+                    let mut params = Params::parse(line, rest)?;
+                    let mat = parse_material(line, kind, &mut params, &textures)?;
+                    params.finish()?;
+                    if materials.insert(name, mat).is_some() {
+                        return Err(error(line, format!("material '{}' already defined", name)));
+                    }
+                }
+                "texture" => {
+                    let (name, kind, rest) = match args {
+                        [name, kind, rest @ ..] => (*name, *kind, rest),
+                        _ => return Err(error(line, "expected 'texture <name> <type>'".into())),
+                    };
+                    let mut params = Params::parse(line, rest)?;
+                    let texture = parse_texture(line, kind, &mut params, &textures, base)?;
+                    params.finish()?;
+                    if textures.insert(name, texture).is_some() {
+                        return Err(error(line, format!("texture '{}' already defined", name)));
+                    }
+                }
+                "background" => {
+                    let Some((kind, rest)) = args.split_first() else {
+                        return Err(error(line, "expected 'background <type>'".into()));
+                    };
+                    let mut params = Params::parse(line, rest)?;
+                    background = parse_background(line, kind, &mut params, base)?;
+                    params.finish()?;
+                }
+                _ => {
+                    let mut params = Params::parse(line, args)?;
+                    let transform = parse_transform(&mut params)?;
+                    // Offset moved from time 0 to 1, across the camera shutter
+                    let motion = params.opt_vec3("motion")?;
+                    if motion.is_some() && params.values.contains_key("center_end") {
+                        return Err(error(
+                            line,
+                            "'center_end' and 'motion' are exclusive".into(),
+                        ));
+                    }
+                    // A density fills the object with its material as a medium
+                    let medium = match params.opt_f64("density")? {
+                        Some(density) if density <= 0.0 => {
+                            return Err(error(line, "'density' must be positive".into()))
+                        }
+                        Some(density) => {
+                            let name = params.values.get("material").copied();
+                            let name =
+                                name.ok_or_else(|| error(line, "missing 'material'".into()))?;
+                            Some((density, lookup(&materials, line, name)?))
+                        }
+                        None => None,
+                    };
+                    let object = match directive {
+                        // Relative to the scene file, the material only covers
+                        // faces the model leaves without one
+                        "mesh" => {
+                            let path = base.join(params.str("path")?);
+                            let name = if params.values.contains_key("material") {
+                                Some(params.str("material")?)
+                            } else {
+                                None
+                            };
+                            // Repeated meshes share one copy of their triangles
+                            let key = (path, name);
+                            let model = match meshes.get(&key) {
+                                Some(model) => Arc::clone(model),
+                                None => {
+                                    let mat = match name {
+                                        Some(name) => lookup(&materials, line, name)?,
+                                        None => MaterialType::lambertian(Color::new(0.8, 0.8, 0.8)),
+                                    };
+                                    let model = load_model(&key.0, mat)
+                                        .map_err(|e| error(line, e.to_string()))?;
+                                    let model = Arc::new(model);
+                                    meshes.insert(key, Arc::clone(&model));
+                                    model
+                                }
+                            };
+                            Box::new(model)
+                        }
+                        _ => parse_shape(line, directive, &mut params, &materials)?,
+                    };
+                    params.finish()?;
+                    let object: Box<dyn Hittable> = match transform {
+                        Some(transform) => Box::new(Transformed::new(object, transform)),
+                        None => object,
+                    };
+                    // Outside the transform, so density is per world unit
+                    let object: Box<dyn Hittable> = match medium {
+                        Some((density, phase)) => {
+                            Box::new(ConstantMedium::new(object, density, phase))
+                        }
+                        None => object,
+                    };
+                    match motion {
+                        Some(motion) => world.add_obj(Moving::new(object, motion)),
+                        None => world.add_boxed(object),
+                    }
+                }
+            }
+        }
+
+        Ok(Scene {
+            camera,
+            world,
+            background,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        color::Color,
+        hittable::HitRecord,
+        image::Image,
+        interval::Interval,
+        material::Material,
+        output::{pfm::write_pfm, png::write_png},
+        ray::Ray,
+        utils::INFINITY,
+        vec3::Point3,
+    };
+
+    fn parse_error(source: &str) -> (usize, String) {
+        match Scene::parse(source) {
+            Err(SceneError::Parse { line, message }) => (line, message),
+            Err(e) => panic!("unexpected error {}", e),
+            Ok(_) => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn parses_camera_materials_and_objects() {
+        let scene = Scene::parse(
+            "# test scene\n\
+             camera image_width=320 samples_per_pixel=8 look_from=13,2,3\n\
+             \n\
+             material ground lambertian albedo=0.5,0.5,0.5\n\
+             material glass dielectric refraction_index=1.5 # comment\n\
+             sphere center=0,-1000,0 radius=1000 material=ground\n\
+             sphere center=0,1,0 radius=1 material=glass\n\
+             triangle v0=0,0,0 v1=1,0,0 v2=0,1,0 material=ground\n\
+             plane point=0,0,0 normal=0,1,0 material=ground\n\
+             quad corner=0,0,0 u=1,0,0 v=0,1,0 material=glass\n\
+             disk center=0,2,0 normal=0,-1,0 radius=0.5 material=glass\n\
+             box min=0,0,0 max=1,1,1 material=ground\n\
+             cylinder base=2,0,0 radius=0.5 height=1 capped=false material=ground\n\
+             cone base=-2,0,0 radius=0.5 height=1 material=ground\n\
+             torus center=0,1,2 major_radius=1 minor_radius=0.25 material=glass\n",
+        )
+        .unwrap();
+
+        assert_eq!(scene.camera.image_width, 320);
+        assert_eq!(scene.camera.samples_per_pixel, 8);
+        assert_eq!(scene.camera.look_from.x(), 13.0);
+        assert_eq!(scene.camera.recursion_depth, 50);
+        assert_eq!(scene.world.len(), 10);
+    }
+
+    #[test]
+    fn loads_example_scene() {
+        let scene = Scene::load(Path::new("scenes/spheres.scene")).unwrap();
+        assert_eq!(scene.world.len(), 9);
+        assert_eq!(scene.camera.defocus_angle, 0.6);
+    }
+
+    #[test]
+    fn loads_cornell_box() {
+        let scene = Scene::load(Path::new("scenes/cornell.scene")).unwrap();
+        assert_eq!(scene.world.len(), 9);
+        assert_eq!(scene.camera.defocus_angle, 0.0);
+    }
+
+    #[test]
+    fn loads_texture_scene() {
+        let scene = Scene::load(Path::new("scenes/textures.scene")).unwrap();
+        assert_eq!(scene.world.len(), 5);
+    }
+
+    #[test]
+    fn loads_fog_scene() {
+        let scene = Scene::load(Path::new("scenes/fog.scene")).unwrap();
+        assert_eq!(scene.world.len(), 7);
+    }
+
+    #[test]
+    fn loads_room_scene() {
+        let scene = Scene::load(Path::new("scenes/room.scene")).unwrap();
+        assert_eq!(scene.world.len(), 8);
+        assert_eq!(scene.camera.aspect_ratio, 1.0);
+    }
+
+    #[test]
+    fn loads_meshes_relative_to_scene() {
+        let dir = std::env::temp_dir().join(format!("scene-mesh-{}", std::process::id()));
+        fs::create_dir_all(dir.join("models")).unwrap();
+        fs::write(
+            dir.join("models/tri.obj"),
+            "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("test.scene"),
+            "material m lambertian albedo=1,0,0\n\
+             mesh path=models/tri.obj material=m\n\
+             mesh path=models/missing.obj\n",
+        )
+        .unwrap();
+
+        let err = Scene::load(&dir.join("test.scene")).err().unwrap();
+        assert!(err.to_string().starts_with("line 3: "));
+        assert!(err.to_string().contains("missing.obj"));
+
+        let source = "mesh path=models/tri.obj\nmesh path=models/tri.obj translate=0,0,-1\n";
+        let scene = Scene::parse_in(source, &dir).unwrap();
+        assert_eq!(scene.world.len(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn places_objects_with_transforms() {
+        let scene = Scene::parse(
+            "material m lambertian albedo=1,1,1\n\
+             sphere center=0,0,0 radius=1 material=m scale=2,1,1 rotate=0,90,0 translate=0,0,-5\n\
+             box min=0,0,0 max=1,1,1 material=m scale=3\n",
+        )
+        .unwrap();
+        let world = scene.world;
+
+        // The stretched axis of the ellipsoid now runs along z
+        let r = Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        let mut rec = HitRecord::default();
+        world.hit(&r, &Interval::new(0.001, INFINITY), &mut rec);
+        assert!(rec.is_hit);
+        assert!((rec.t - 3.0).abs() < 1e-9);
+        assert!((world.bounding_box().x.max - 3.0).abs() < 1e-3);
+
+        assert_eq!(
+            parse_error(
+                "material m lambertian albedo=1,1,1\n\
+                 sphere center=0,0,0 radius=1 material=m scale=0,1,1\n"
+            ),
+            (2, "transform is not invertible".to_string())
+        );
+        assert_eq!(
+            parse_error(
+                "material m lambertian albedo=1,1,1\n\
+                 sphere center=0,0,0 radius=1 material=m rotate=90\n"
+            )
+            .1,
+            "'rotate' expects three comma separated numbers, found '90'"
+        );
+    }
+
+    #[test]
+    fn moving_objects_and_shutter() {
+        let scene = Scene::parse(
+            "camera shutter_open=0 shutter_close=1\n\
+             material m lambertian albedo=1,1,1\n\
+             sphere center=0,0,-5 center_end=4,0,-5 radius=1 material=m\n\
+             box min=-1,-1,-1 max=1,1,1 material=m translate=0,0,-10 motion=0,4,0\n",
+        )
+        .unwrap();
+        assert_eq!(scene.camera.shutter_close, 1.0);
+
+        let cast = |x: f64, y: f64, time: f64| {
+            let r = Ray::with_time(Point3::new(x, y, 0.0), Vec3::new(0.0, 0.0, -1.0), time);
+            let mut rec = HitRecord::default();
+            scene
+                .world
+                .hit(&r, &Interval::new(0.001, INFINITY), &mut rec);
+            (rec.is_hit, rec.t)
+        };
+        assert!(!cast(4.0, 0.0, 0.0).0);
+        assert_eq!(cast(4.0, 0.0, 1.0), (true, 4.0));
+        assert!(!cast(0.0, 4.0, 0.0).0);
+        assert_eq!(cast(0.0, 4.0, 1.0), (true, 9.0));
+
+        assert_eq!(
+            parse_error(
+                "material m lambertian albedo=1,1,1\n\
+                 sphere center=0,0,0 center_end=1,0,0 motion=0,1,0 radius=1 material=m\n"
+            ),
+            (2, "'center_end' and 'motion' are exclusive".to_string())
+        );
+    }
+
+    #[test]
+    fn textured_materials() {
+        let scene = Scene::parse(
+            "texture white solid color=1,1,1\n\
+             texture tiles checker scale=1 even=white odd=0,0,0\n\
+             texture globe uv_checker width=2 height=1 even=tiles odd=1,0,0\n\
+             material floor lambertian texture=tiles\n\
+             material shiny metal texture=globe fuzz=0.1\n\
+             plane point=0,0,0 normal=0,1,0 material=floor\n\
+             sphere center=0,5,0 radius=1 material=shiny\n",
+        )
+        .unwrap();
+        let hit = |origin: Point3| {
+            let r = Ray::new(origin, Vec3::new(0.0, -1.0, 0.0));
+            let mut rec = HitRecord::default();
+            scene
+                .world
+                .hit(&r, &Interval::new(0.001, INFINITY), &mut rec);
+            rec
+        };
+        let rec = hit(Point3::new(0.5, 1.0, 0.5));
+        let scattered = rec
+            .mat
+            .scatter(&Ray::new(Point3::default(), Vec3::default()), &rec);
+        assert_eq!(scattered.attenuation.x(), 1.0);
+        let rec = hit(Point3::new(1.5, 1.0, 0.5));
+        let scattered = rec
+            .mat
+            .scatter(&Ray::new(Point3::default(), Vec3::default()), &rec);
+        assert_eq!(scattered.attenuation.x(), 0.0);
+
+        assert_eq!(
+            parse_error("material m lambertian texture=wood\n"),
+            (1, "undefined texture 'wood'".to_string())
+        );
+        assert_eq!(
+            parse_error("texture t checker scale=0 even=1,1,1 odd=0,0,0\n").1,
+            "'scale' must be positive"
+        );
+        assert_eq!(
+            parse_error("texture t solid color=1,1,1\ntexture t solid color=0,0,0\n"),
+            (2, "texture 't' already defined".to_string())
+        );
+        assert_eq!(
+            parse_error("\ntexture t uv_checker width=-1\n"),
+            (
+                2,
+                "'width' must be a positive integer, found '-1'".to_string()
+            )
+        );
+        assert_eq!(
+            parse_error("texture t uv_checker height=0\n").1,
+            "'height' must be a positive integer, found '0'"
+        );
+        assert_eq!(
+            parse_error("texture t plaid\n").1,
+            "unknown texture type 'plaid'"
+        );
+        assert_eq!(
+            parse_error("texture t marble seed=-1\n").1,
+            "'seed' must be a non-negative integer, found '-1'"
+        );
+    }
+
+    #[test]
+    fn normal_mapped_materials() {
+        // Normals tilted halfway towards the plane's tangent, +x
+        let scene = Scene::parse(
+            "texture tilt solid color=1,0.5,1\n\
+             material mirror metal albedo=1,1,1 normal_map=tilt\n\
+             plane point=0,0,0 normal=0,1,0 material=mirror\n",
+        )
+        .unwrap();
+        let r = Ray::new(Point3::new(0.0, 1.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        let mut rec = HitRecord::default();
+        scene
+            .world
+            .hit(&r, &Interval::new(0.001, INFINITY), &mut rec);
+        let scattered = rec.mat.scatter(&r, &rec);
+        assert!(scattered.is_scattered);
+        let dir = Vec3::unit_vector(scattered.ray.dir());
+        assert!((dir - Vec3::new(1.0, 0.0, 0.0)).near_zero());
+
+        assert!(Scene::parse(
+            "texture h perlin scale=4\n\
+             material m lambertian albedo=1,1,1 bump=h bump_scale=0.1\n"
+        )
+        .is_ok());
+        assert_eq!(
+            parse_error(
+                "texture h perlin\n\
+                 material m dielectric refraction_index=1.5 bump=h normal_map=h\n"
+            ),
+            (2, "'normal_map' and 'bump' are exclusive".to_string())
+        );
+        assert_eq!(
+            parse_error("material m lambertian albedo=1,1,1 bump_scale=2\n").1,
+            "unknown key 'bump_scale'"
+        );
+        assert_eq!(
+            parse_error("material m lambertian albedo=1,1,1 normal_map=bricks\n").1,
+            "undefined texture 'bricks'"
+        );
+    }
+
+    #[test]
+    fn volumes() {
+        let scene = Scene::parse(
+            "material smoke isotropic albedo=0.2,0.4,0.6\n\
+             sphere center=0,0,0 radius=1 material=smoke density=1e6\n\
+             box min=0,0,0 max=1,1,1 material=smoke density=1e6 scale=2 translate=0,0,-10\n",
+        )
+        .unwrap();
+        let hit = |origin: Point3| {
+            let r = Ray::new(origin, Vec3::new(0.0, 0.0, -1.0));
+            let mut rec = HitRecord::default();
+            scene
+                .world
+                .hit(&r, &Interval::new(0.001, INFINITY), &mut rec);
+            rec
+        };
+        // Dense enough to scatter right at the boundary
+        let rec = hit(Point3::new(0.0, 0.0, 5.0));
+        assert!(rec.is_hit && (rec.p.z() - 1.0).abs() < 1e-3);
+        let scattered = rec.mat.scatter(&Ray::new(rec.p, Vec3::default()), &rec);
+        assert_eq!(scattered.attenuation.z(), 0.6);
+        // The box was scaled and moved before being filled
+        let rec = hit(Point3::new(1.5, 1.5, 0.0));
+        assert!(rec.is_hit && (rec.p.z() + 8.0).abs() < 1e-3);
+
+        assert_eq!(
+            parse_error(
+                "material m isotropic albedo=1,1,1\n\
+                 sphere center=0,0,0 radius=1 material=m density=0\n"
+            ),
+            (2, "'density' must be positive".to_string())
+        );
+        assert_eq!(
+            parse_error(
+                "material m isotropic albedo=1,1,1\n\
+                 sphere center=0,0,0 radius=1 material=fog density=1\n"
+            )
+            .1,
+            "undefined material 'fog'"
+        );
+    }
+
+    #[test]
+    fn image_textures() {
+        let dir = std::env::temp_dir().join(format!("scene-texture-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let image = Image::from_pixels(1, 1, vec![Color::new(0.25, 0.25, 0.25)]);
+        let mut bytes = Vec::new();
+        write_png(&mut bytes, &image).unwrap();
+        fs::write(dir.join("grey.png"), bytes).unwrap();
+
+        let albedo = |texture: &str| {
+            let source = format!(
+                "texture t image path=grey.png {}\n\
+                 material m lambertian texture=t\n\
+                 sphere center=0,0,-2 radius=1 material=m\n",
+                texture
+            );
+            let scene = Scene::parse_in(&source, &dir).unwrap();
+            let r = Ray::new(Point3::default(), Vec3::new(0.0, 0.0, -1.0));
+            let mut rec = HitRecord::default();
+            scene
+                .world
+                .hit(&r, &Interval::new(0.001, INFINITY), &mut rec);
+            rec.mat.scatter(&r, &rec).attenuation.x()
+        };
+        // Stored as 128 after gamma encoding, decoded back to about 0.25
+        assert!((albedo("filter=nearest") - 0.2520).abs() < 1e-3);
+        assert!((albedo("wrap=mirror srgb=false") - 128.0 / 255.0).abs() < 1e-9);
+
+        let err = Scene::parse_in("texture t image path=grey.png wrap=tile\n", &dir)
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 1: 'wrap' expects repeat, clamp or mirror, found 'tile'"
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn backgrounds() {
+        let scene = Scene::parse("background solid color=0,0,0\n").unwrap();
+        let c = scene.background.color(&Vec3::new(0.0, 1.0, 0.0));
+        assert_eq!((c.x(), c.y(), c.z()), (0.0, 0.0, 0.0));
+        let scene = Scene::parse("background gradient bottom=1,0,0 top=0,0,1\n").unwrap();
+        let c = scene.background.color(&Vec3::new(0.0, 1.0, 0.0));
+        assert_eq!((c.x(), c.y(), c.z()), (0.0, 0.0, 1.0));
+
+        let dir = std::env::temp_dir().join(format!("scene-env-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut bytes = Vec::new();
+        let image = Image::from_pixels(1, 1, vec![Color::new(0.25, 0.5, 1.0)]);
+        write_pfm(&mut bytes, &image).unwrap();
+        fs::write(dir.join("sky.pfm"), bytes).unwrap();
+        let scene =
+            Scene::parse_in("background environment path=sky.pfm intensity=2\n", &dir).unwrap();
+        let c = scene.background.color(&Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!((c.x(), c.y(), c.z()), (0.5, 1.0, 2.0));
+
+        let err = Scene::parse_in("\nbackground environment path=none.pfm\n", &dir)
+            .err()
+            .unwrap();
+        assert!(err.to_string().starts_with("line 2: "));
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            parse_error("background stars\n"),
+            (1, "unknown background type 'stars'".to_string())
+        );
+        assert_eq!(
+            parse_error("background\n").1,
+            "expected 'background <type>'"
+        );
+    }
+
+    #[test]
+    fn errors_carry_line_numbers() {
+        assert_eq!(
+            parse_error("camera vfov=20\n\nsphere center=0,0,0 radius=1 material=missing\n"),
+            (3, "undefined material 'missing'".to_string())
+        );
+        assert_eq!(
+            parse_error("material m metal albedo=1,1\n"),
+            (
+                1,
+                "'albedo' expects three comma separated numbers, found '1,1'".to_string()
+            )
+        );
+        assert_eq!(
+            parse_error("\ncamera vfov=wide\n"),
+            (2, "'vfov' expects a number, found 'wide'".to_string())
+        );
+        assert_eq!(parse_error("cube size=1\n").1, "unknown directive 'cube'");
+        assert_eq!(parse_error("camera zoom=2\n").1, "unknown key 'zoom'");
+        assert_eq!(
+            parse_error(
+                "material m lambertian albedo=1,1,1\n\
+                 cone base=0,0,0 radius=1 height=1 capped=no material=m\n"
+            ),
+            (2, "'capped' expects true or false, found 'no'".to_string())
+        );
+        assert_eq!(parse_error("material m lambertian\n").1, "missing 'albedo'");
+        assert_eq!(
+            parse_error(
+                "material m lambertian albedo=1,1,1\n\
+                 cylinder base=0,0,0 radius=0 height=1 material=m\n"
+            ),
+            (2, "'radius' must be positive".to_string())
+        );
+        assert_eq!(
+            parse_error(
+                "material m lambertian albedo=1,1,1\n\
+                 cone base=0,0,0 radius=1 height=-2 material=m\n"
+            )
+            .1,
+            "'height' must be positive"
+        );
+        assert_eq!(
+            parse_error("camera image_width=-5\n").1,
+            "'image_width' must be a positive integer, found '-5'"
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use crate::{color::Color, vec3::Point3};
+
+use super::{Texture, TextureType};
+
+// Alternates two textures in cubes of side `scale` filling space, so the
+// pattern does not depend on how a surface is parameterised
+#[derive(Clone)]
+pub struct Checker {
+    inv_scale: f64,
+    even: Arc<TextureType>,
+    odd: Arc<TextureType>,
+}
+
+impl Checker {
+    pub fn new(scale: f64, even: TextureType, odd: TextureType) -> Self {
+        Checker {
+            inv_scale: 1.0 / scale,
+            even: Arc::new(even),
+            odd: Arc::new(odd),
+        }
+    }
+}
+
+impl Texture for Checker {
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color {
+        let cell = (0..3)
+            .map(|axis| (self.inv_scale * p[axis]).floor() as i64)
+            .sum::<i64>();
+        if cell.rem_euclid(2) == 0 {
+            self.even.value(u, v, p)
+        } else {
+            self.odd.value(u, v, p)
+        }
+    }
+}
+
+// Checkerboard in surface coordinates with `width` by `height` squares over
+// the unit (u, v) square, following the surface as it curves
+#[derive(Clone)]
+pub struct UvChecker {
+    width: f64,
+    height: f64,
+    even: Arc<TextureType>,
+    odd: Arc<TextureType>,
+}
+
+impl UvChecker {
+    pub fn new(width: u32, height: u32, even: TextureType, odd: TextureType) -> Self {
+        UvChecker {
+            width: width as f64,
+            height: height as f64,
+            even: Arc::new(even),
+            odd: Arc::new(odd),
+        }
+    }
+}
+
+impl Texture for UvChecker {
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color {
+        let cell = (u * self.width).floor() as i64 + (v * self.height).floor() as i64;
+        if cell.rem_euclid(2) == 0 {
+            self.even.value(u, v, p)
+        } else {
+            self.odd.value(u, v, p)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn black_white() -> (TextureType, TextureType) {
+        (
+            TextureType::solid(Color::new(0.0, 0.0, 0.0)),
+            TextureType::solid(Color::new(1.0, 1.0, 1.0)),
+        )
+    }
+
+    #[test]
+    fn spatial_checker_alternates_cells() {
+        let (even, odd) = black_white();
+        let checker = Checker::new(0.5, even, odd);
+        let at = |x: f64, y: f64, z: f64| checker.value(0.0, 0.0, &Point3::new(x, y, z)).x();
+        assert_eq!(at(0.1, 0.1, 0.1), 0.0);
+        assert_eq!(at(0.6, 0.1, 0.1), 1.0);
+        assert_eq!(at(0.6, 0.6, 0.1), 0.0);
+        // Negative coordinates continue the pattern instead of mirroring it
+        assert_eq!(at(-0.1, 0.1, 0.1), 1.0);
+        assert_eq!(at(-0.6, 0.1, 0.1), 0.0);
+    }
+
+    #[test]
+    fn uv_checker_ignores_position() {
+        let (even, odd) = black_white();
+        let checker = UvChecker::new(4, 2, even, odd);
+        let p = Point3::new(0.3, 7.0, -2.0);
+        assert_eq!(checker.value(0.1, 0.1, &p).x(), 0.0);
+        assert_eq!(checker.value(0.3, 0.1, &p).x(), 1.0);
+        assert_eq!(checker.value(0.3, 0.6, &p).x(), 0.0);
+        assert_eq!(checker.value(0.9, 0.9, &p).x(), 0.0);
+    }
+}
@@ -0,0 +1,163 @@
+use std::sync::Arc;
+
+use crate::{color::Color, image::Image, vec3::Point3};
+
+use super::Texture;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Filter {
+    Nearest,
+    // Blends the four texels around the lookup point
+    #[default]
+    Bilinear,
+}
+
+// What texel coordinates outside the image map to
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Wrap {
+    #[default]
+    Repeat,
+    Clamp,
+    // Repeats with every other copy flipped, hiding the seams
+    Mirror,
+}
+
+impl Wrap {
+    fn apply(self, i: i64, size: usize) -> usize {
+        let n = size as i64;
+        let i = match self {
+            Wrap::Repeat => i.rem_euclid(n),
+            Wrap::Clamp => i.clamp(0, n - 1),
+            Wrap::Mirror => {
+                let m = i.rem_euclid(2 * n);
+                if m < n {
+                    m
+                } else {
+                    2 * n - 1 - m
+                }
+            }
+        };
+        i as usize
+    }
+}
+
+// Maps an image over the unit (u, v) square with v = 0 along its bottom
+// row. The pixels are shared, so cloning the texture is cheap.
+#[derive(Clone)]
+pub struct ImageTexture {
+    image: Arc<Image>,
+    filter: Filter,
+    wrap: Wrap,
+}
+
+impl ImageTexture {
+    pub fn new(image: Arc<Image>, filter: Filter, wrap: Wrap) -> Self {
+        ImageTexture {
+            image,
+            filter,
+            wrap,
+        }
+    }
+
+    fn texel(&self, x: i64, y: i64) -> Color {
+        self.image.get(
+            self.wrap.apply(x, self.image.width()),
+            self.wrap.apply(y, self.image.height()),
+        )
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, _p: &Point3) -> Color {
+        let (width, height) = (self.image.width() as f64, self.image.height() as f64);
+        if width == 0.0 || height == 0.0 {
+            // Cyan makes missing textures easy to spot
+            return Color::new(0.0, 1.0, 1.0);
+        }
+        let x = u * width;
+        let y = (1.0 - v) * height;
+        match self.filter {
+            Filter::Nearest => self.texel(x.floor() as i64, y.floor() as i64),
+            Filter::Bilinear => {
+                // Texel centres sit half a texel in from their corners
+                let (x, y) = (x - 0.5, y - 0.5);
+                let (x0, y0) = (x.floor(), y.floor());
+                let (fx, fy) = (x - x0, y - y0);
+                let (x0, y0) = (x0 as i64, y0 as i64);
+                let top = (1.0 - fx) * self.texel(x0, y0) + fx * self.texel(x0 + 1, y0);
+                let bottom = (1.0 - fx) * self.texel(x0, y0 + 1) + fx * self.texel(x0 + 1, y0 + 1);
+                (1.0 - fy) * top + fy * bottom
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texture(filter: Filter, wrap: Wrap) -> ImageTexture {
+        let image = Image::from_pixels(
+            2,
+            2,
+            vec![
+                Color::new(1.0, 0.0, 0.0),
+                Color::new(0.0, 1.0, 0.0),
+                Color::new(0.0, 0.0, 1.0),
+                Color::new(1.0, 1.0, 1.0),
+            ],
+        );
+        ImageTexture::new(Arc::new(image), filter, wrap)
+    }
+
+    #[test]
+    fn samples_nearest_pixel_with_v_up() {
+        let texture = texture(Filter::Nearest, Wrap::Clamp);
+        let p = Point3::default();
+        // Top-left pixel at high v
+        assert_eq!(texture.value(0.2, 0.9, &p).x(), 1.0);
+        assert_eq!(texture.value(0.7, 0.9, &p).y(), 1.0);
+        assert_eq!(texture.value(0.2, 0.1, &p).z(), 1.0);
+        // Out of range coordinates clamp to the edge
+        assert_eq!(texture.value(1.5, -3.0, &p).x(), 1.0);
+        assert_eq!(texture.value(1.5, -3.0, &p).y(), 1.0);
+    }
+
+    #[test]
+    fn wrap_modes() {
+        let p = Point3::default();
+        let repeat = texture(Filter::Nearest, Wrap::Repeat);
+        assert_eq!(repeat.value(1.2, 0.9, &p).x(), 1.0);
+        assert_eq!(repeat.value(-0.2, 0.9, &p).y(), 1.0);
+        let mirror = texture(Filter::Nearest, Wrap::Mirror);
+        assert_eq!(mirror.value(1.2, 0.9, &p).y(), 1.0);
+        assert_eq!(mirror.value(1.7, 0.9, &p).x(), 1.0);
+        assert_eq!(mirror.value(-0.2, 0.9, &p).x(), 1.0);
+
+        assert_eq!(Wrap::Mirror.apply(-1, 3), 0);
+        assert_eq!(Wrap::Mirror.apply(5, 3), 0);
+        assert_eq!(Wrap::Repeat.apply(-1, 3), 2);
+    }
+
+    #[test]
+    fn bilinear_blends_neighbours() {
+        let p = Point3::default();
+        let clamp = texture(Filter::Bilinear, Wrap::Clamp);
+        // Texel centres return the texel itself
+        let c = clamp.value(0.25, 0.75, &p);
+        assert_eq!((c.x(), c.y(), c.z()), (1.0, 0.0, 0.0));
+        // Halfway between the two top texels
+        let c = clamp.value(0.5, 0.75, &p);
+        assert_eq!((c.x(), c.y(), c.z()), (0.5, 0.5, 0.0));
+        // The image centre averages all four
+        let c = clamp.value(0.5, 0.5, &p);
+        assert_eq!((c.x(), c.y(), c.z()), (0.5, 0.5, 0.5));
+
+        // Across the seam, repeat blends in the opposite edge
+        let repeat = texture(Filter::Bilinear, Wrap::Repeat);
+        let c = repeat.value(0.0, 0.75, &p);
+        assert_eq!((c.x(), c.y()), (0.5, 0.5));
+        let c = clamp.value(0.0, 0.75, &p);
+        assert_eq!((c.x(), c.y()), (1.0, 0.0));
+    }
+}
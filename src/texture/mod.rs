@@ -0,0 +1,72 @@
+use std::sync::Arc;
+
+use checker::{Checker, UvChecker};
+use image_texture::{Filter, ImageTexture, Wrap};
+use noise::{NoisePattern, NoiseTexture};
+use solid::SolidColor;
+use worley::WorleyTexture;
+
+use crate::{color::Color, image::Image, vec3::Point3};
+
+pub mod checker;
+pub mod image_texture;
+pub mod noise;
+pub mod perlin;
+pub mod solid;
+pub mod worley;
+
+// Colour source for material albedo, sampled at the surface coordinates
+// (u, v) and world position p of a hit
+pub trait Texture: Send + Sync {
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color;
+}
+
+#[derive(Clone)]
+pub enum TextureType {
+    Solid(SolidColor),
+    Checker(Checker),
+    UvChecker(UvChecker),
+    Image(ImageTexture),
+    Noise(NoiseTexture),
+    Worley(WorleyTexture),
+}
+
+impl TextureType {
+    pub fn solid(color: Color) -> Self {
+        TextureType::Solid(SolidColor::new(color))
+    }
+    pub fn checker(scale: f64, even: TextureType, odd: TextureType) -> Self {
+        TextureType::Checker(Checker::new(scale, even, odd))
+    }
+    pub fn uv_checker(width: u32, height: u32, even: TextureType, odd: TextureType) -> Self {
+        TextureType::UvChecker(UvChecker::new(width, height, even, odd))
+    }
+    pub fn image(image: Arc<Image>, filter: Filter, wrap: Wrap) -> Self {
+        TextureType::Image(ImageTexture::new(image, filter, wrap))
+    }
+    pub fn noise(pattern: NoisePattern, scale: f64, seed: u64, low: Color, high: Color) -> Self {
+        TextureType::Noise(NoiseTexture::new(pattern, scale, seed, low, high))
+    }
+    pub fn worley(scale: f64, seed: u64, low: Color, high: Color) -> Self {
+        TextureType::Worley(WorleyTexture::new(scale, seed, low, high))
+    }
+}
+
+impl From<Color> for TextureType {
+    fn from(color: Color) -> Self {
+        TextureType::solid(color)
+    }
+}
+
+impl Texture for TextureType {
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color {
+        match self {
+            TextureType::Solid(t) => t.value(u, v, p),
+            TextureType::Checker(t) => t.value(u, v, p),
+            TextureType::UvChecker(t) => t.value(u, v, p),
+            TextureType::Image(t) => t.value(u, v, p),
+            TextureType::Noise(t) => t.value(u, v, p),
+            TextureType::Worley(t) => t.value(u, v, p),
+        }
+    }
+}
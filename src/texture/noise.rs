@@ -0,0 +1,104 @@
+use std::sync::Arc;
+
+use crate::{color::Color, vec3::Point3};
+
+use super::{perlin::Perlin, Texture};
+
+// Octaves summed for turbulence
+const TURBULENCE_DEPTH: usize = 7;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NoisePattern {
+    // Plain gradient noise, soft blobs
+    Perlin,
+    // Several octaves of noise, cloud-like
+    Turbulence,
+    // Stripes along z bent by turbulence
+    Marble,
+    // Rings around the y axis, wobbled by noise
+    Wood,
+}
+
+// Blends between the `low` and `high` colours by a Perlin noise pattern,
+// with `scale` as the frequency of its features
+#[derive(Clone)]
+pub struct NoiseTexture {
+    perlin: Arc<Perlin>,
+    pattern: NoisePattern,
+    scale: f64,
+    low: Color,
+    high: Color,
+}
+
+impl NoiseTexture {
+    pub fn new(pattern: NoisePattern, scale: f64, seed: u64, low: Color, high: Color) -> Self {
+        NoiseTexture {
+            perlin: Arc::new(Perlin::new(seed)),
+            pattern,
+            scale,
+            low,
+            high,
+        }
+    }
+
+    // Blend factor in [0, 1]
+    fn amount(&self, p: &Point3) -> f64 {
+        let q = self.scale * *p;
+        let t = match self.pattern {
+            NoisePattern::Perlin => 0.5 * (1.0 + self.perlin.noise(&q)),
+            NoisePattern::Turbulence => self.perlin.turbulence(&q, TURBULENCE_DEPTH),
+            NoisePattern::Marble => {
+                let turbulence = self.perlin.turbulence(p, TURBULENCE_DEPTH);
+                0.5 * (1.0 + f64::sin(q.z() + 10.0 * turbulence))
+            }
+            NoisePattern::Wood => {
+                let rings = f64::hypot(q.x(), q.z()) + 0.5 * self.perlin.noise(&q);
+                // Sharp edge on the outside of each ring, fading inwards
+                rings.rem_euclid(1.0).powi(3)
+            }
+        };
+        t.clamp(0.0, 1.0)
+    }
+}
+
+impl Texture for NoiseTexture {
+    fn value(&self, _u: f64, _v: f64, p: &Point3) -> Color {
+        let t = self.amount(p);
+        (1.0 - t) * self.low + t * self.high
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patterns_stay_between_colours() {
+        let low = Color::new(0.1, 0.2, 0.3);
+        let high = Color::new(0.9, 0.8, 0.7);
+        for pattern in [
+            NoisePattern::Perlin,
+            NoisePattern::Turbulence,
+            NoisePattern::Marble,
+            NoisePattern::Wood,
+        ] {
+            let texture = NoiseTexture::new(pattern, 4.0, 9, low, high);
+            let same = NoiseTexture::new(pattern, 4.0, 9, low, high);
+            let (mut min, mut max) = (f64::INFINITY, f64::NEG_INFINITY);
+            for i in 0..500 {
+                let p = Point3::new(
+                    i as f64 * 0.037,
+                    (i % 17) as f64 * 0.11,
+                    -(i as f64) * 0.023,
+                );
+                let c = texture.value(0.0, 0.0, &p);
+                assert!(c.x() >= 0.1 - 1e-12 && c.x() <= 0.9 + 1e-12);
+                assert_eq!(c.y(), same.value(0.0, 0.0, &p).y());
+                min = min.min(c.x());
+                max = max.max(c.x());
+            }
+            // Every pattern actually varies
+            assert!(max - min > 0.2, "{:?} spans {} to {}", pattern, min, max);
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use rand::{rngs::SmallRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::vec3::{Point3, Vec3};
+
+const POINT_COUNT: usize = 256;
+
+// Gradient noise over a lattice of random unit vectors, smoothly varying
+// within roughly [-1, 1] and zero at the lattice points. The tables come
+// from `seed`, so the same seed always gives the same pattern.
+pub struct Perlin {
+    gradients: Vec<Vec3>,
+    perm_x: Vec<usize>,
+    perm_y: Vec<usize>,
+    perm_z: Vec<usize>,
+}
+
+impl Perlin {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let gradients = (0..POINT_COUNT)
+            .map(|_| loop {
+                // Uniform over the sphere by rejection from the cube
+                let v = Vec3::new(
+                    rng.random_range(-1.0..1.0),
+                    rng.random_range(-1.0..1.0),
+                    rng.random_range(-1.0..1.0),
+                );
+                let len = v.length();
+                if len > 1e-3 && len <= 1.0 {
+                    break v / len;
+                }
+            })
+            .collect();
+        let mut permutation = || {
+            let mut perm: Vec<usize> = (0..POINT_COUNT).collect();
+            perm.shuffle(&mut rng);
+            perm
+        };
+        Perlin {
+            perm_x: permutation(),
+            perm_y: permutation(),
+            perm_z: permutation(),
+            gradients,
+        }
+    }
+
+    pub fn noise(&self, p: &Point3) -> f64 {
+        let (fx, fy, fz) = (p.x().floor(), p.y().floor(), p.z().floor());
+        let (u, v, w) = (p.x() - fx, p.y() - fy, p.z() - fz);
+        let (i, j, k) = (fx as i64, fy as i64, fz as i64);
+        let mask = POINT_COUNT as i64 - 1;
+
+        // Hermite smoothing hides the lattice
+        let (uu, vv, ww) = (
+            u * u * (3.0 - 2.0 * u),
+            v * v * (3.0 - 2.0 * v),
+            w * w * (3.0 - 2.0 * w),
+        );
+        let mut accum = 0.0;
+        for di in 0..2 {
+            for dj in 0..2 {
+                for dk in 0..2 {
+                    let gradient = &self.gradients[self.perm_x[((i + di) & mask) as usize]
+                        ^ self.perm_y[((j + dj) & mask) as usize]
+                        ^ self.perm_z[((k + dk) & mask) as usize]];
+                    let (a, b, c) = (di as f64, dj as f64, dk as f64);
+                    let offset = Vec3::new(u - a, v - b, w - c);
+                    accum += (a * uu + (1.0 - a) * (1.0 - uu))
+                        * (b * vv + (1.0 - b) * (1.0 - vv))
+                        * (c * ww + (1.0 - c) * (1.0 - ww))
+                        * Vec3::dot(gradient, &offset);
+                }
+            }
+        }
+        accum
+    }
+
+    // Sum of `depth` octaves, each at twice the frequency and half the
+    // weight of the last
+    pub fn turbulence(&self, p: &Point3, depth: usize) -> f64 {
+        let mut accum = 0.0;
+        let mut p = *p;
+        let mut weight = 1.0;
+        for _ in 0..depth {
+            accum += weight * self.noise(&p);
+            weight *= 0.5;
+            p = 2.0 * p;
+        }
+        accum.abs()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeded_and_smooth() {
+        let a = Perlin::new(3);
+        let b = Perlin::new(3);
+        let c = Perlin::new(4);
+        let p = Point3::new(1.3, -2.7, 0.45);
+        assert_eq!(a.noise(&p), b.noise(&p));
+        assert_ne!(a.noise(&p), c.noise(&p));
+
+        // Zero on the lattice, bounded and continuous in between
+        assert_eq!(a.noise(&Point3::new(2.0, -5.0, 7.0)), 0.0);
+        let mut previous = a.noise(&p);
+        for i in 1..1000 {
+            let q = p + Vec3::new(i as f64 * 0.001, 0.0, 0.0);
+            let value = a.noise(&q);
+            assert!(value.abs() <= 1.0);
+            assert!((value - previous).abs() < 0.01);
+            previous = value;
+        }
+        assert!(a.turbulence(&p, 7) >= 0.0);
+    }
+}
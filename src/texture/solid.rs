@@ -0,0 +1,20 @@
+use crate::{color::Color, vec3::Point3};
+
+use super::Texture;
+
+#[derive(Clone)]
+pub struct SolidColor {
+    albedo: Color,
+}
+
+impl SolidColor {
+    pub fn new(albedo: Color) -> Self {
+        SolidColor { albedo }
+    }
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _u: f64, _v: f64, _p: &Point3) -> Color {
+        self.albedo
+    }
+}
@@ -0,0 +1,116 @@
+use crate::{color::Color, vec3::Point3};
+
+use super::Texture;
+
+// Cellular noise: space is cut into unit cells holding one pseudo-random
+// feature point each, and the value at p is the distance to the nearest
+// point. The points are hashed from the cell and `seed`, so no tables are
+// needed.
+#[derive(Clone)]
+pub struct Worley {
+    seed: u64,
+}
+
+// SplitMix64 finaliser, spreads every input bit over the output
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+impl Worley {
+    pub fn new(seed: u64) -> Self {
+        Worley { seed }
+    }
+
+    fn feature_point(&self, i: i64, j: i64, k: i64) -> Point3 {
+        let mut h = mix(self.seed.wrapping_add(0x9e37_79b9_7f4a_7c15));
+        for c in [i, j, k] {
+            h = mix(h ^ c as u64);
+        }
+        let unit = |bits: u64| (bits >> 43) as f64 / (1u64 << 21) as f64;
+        Point3::new(
+            i as f64 + unit(h),
+            j as f64 + unit(h << 21),
+            k as f64 + unit(h << 42),
+        )
+    }
+
+    // Distance to the nearest feature point, at most about 1.7 but usually
+    // well under 1
+    pub fn distance(&self, p: &Point3) -> f64 {
+        let (i, j, k) = (
+            p.x().floor() as i64,
+            p.y().floor() as i64,
+            p.z().floor() as i64,
+        );
+        let mut nearest = f64::INFINITY;
+        for di in -1..=1 {
+            for dj in -1..=1 {
+                for dk in -1..=1 {
+                    let point = self.feature_point(i + di, j + dj, k + dk);
+                    nearest = f64::min(nearest, (point - *p).length_squared());
+                }
+            }
+        }
+        nearest.sqrt()
+    }
+}
+
+// Blends from `low` at the feature points to `high` between them, giving
+// cells like stone paving or scales; `scale` sets how many cells fit in a
+// unit of distance
+#[derive(Clone)]
+pub struct WorleyTexture {
+    worley: Worley,
+    scale: f64,
+    low: Color,
+    high: Color,
+}
+
+impl WorleyTexture {
+    pub fn new(scale: f64, seed: u64, low: Color, high: Color) -> Self {
+        WorleyTexture {
+            worley: Worley::new(seed),
+            scale,
+            low,
+            high,
+        }
+    }
+}
+
+impl Texture for WorleyTexture {
+    fn value(&self, _u: f64, _v: f64, p: &Point3) -> Color {
+        let t = self.worley.distance(&(self.scale * *p)).clamp(0.0, 1.0);
+        (1.0 - t) * self.low + t * self.high
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vec3::Vec3;
+
+    #[test]
+    fn zero_at_feature_points() {
+        let worley = Worley::new(11);
+        let point = worley.feature_point(3, -2, 5);
+        assert_eq!(worley.distance(&point), 0.0);
+        assert!(worley.distance(&(point + Vec3::new(0.01, 0.0, 0.0))) <= 0.01 + 1e-12);
+
+        let p = Point3::new(0.5, 1.5, -7.25);
+        assert_eq!(worley.distance(&p), Worley::new(11).distance(&p));
+        assert_ne!(worley.distance(&p), Worley::new(12).distance(&p));
+        assert!(worley.distance(&p) < 3f64.sqrt());
+    }
+
+    #[test]
+    fn texture_is_dark_at_cell_centres() {
+        let texture =
+            WorleyTexture::new(2.0, 5, Color::new(0.0, 0.0, 0.0), Color::new(1.0, 1.0, 1.0));
+        let centre = Worley::new(5).feature_point(1, 0, -3) / 2.0;
+        assert_eq!(texture.value(0.0, 0.0, &centre).x(), 0.0);
+        let elsewhere = texture.value(0.0, 0.0, &(centre + Vec3::new(0.1, 0.1, 0.0)));
+        assert!(elsewhere.x() > 0.0);
+    }
+}
@@ -1,12 +1,23 @@
-use rand::Rng;
+use std::cell::RefCell;
+
+use rand::{rngs::SmallRng, Rng, SeedableRng};
 
 pub const PI: f64 = std::f64::consts::PI;
-pub const INFINITY: f64 = std::f64::INFINITY;
+pub const INFINITY: f64 = f64::INFINITY;
+
+thread_local! {
+    // Per-thread generator so renders can be made reproducible with a seed
+    static RNG: RefCell<SmallRng> = RefCell::new(SmallRng::from_os_rng());
+}
+
+pub fn seed_thread_rng(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = SmallRng::seed_from_u64(seed));
+}
 
 pub fn random_f64() -> f64 {
-    rand::rng().random_range(0.0..1.0)
+    RNG.with(|rng| rng.borrow_mut().random_range(0.0..1.0))
 }
 
 pub fn random_min_max(min: f64, max: f64) -> f64 {
-    rand::rng().random_range(min..max)
+    RNG.with(|rng| rng.borrow_mut().random_range(min..max))
 }
@@ -0,0 +1,202 @@
+use std::ops;
+
+use super::{Point3, Vec3};
+
+// Row-major 4x4 matrix for affine transforms of points and vectors. Products
+// apply right to left: `a * b` transforms by `b` first.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mat4 {
+    m: [[f64; 4]; 4],
+}
+
+impl Mat4 {
+    pub const IDENTITY: Mat4 = Mat4 {
+        m: [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ],
+    };
+
+    pub fn new(m: [[f64; 4]; 4]) -> Self {
+        Mat4 { m }
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> f64 {
+        self.m[row][col]
+    }
+
+    pub fn translation(offset: Vec3) -> Self {
+        let mut m = Mat4::IDENTITY;
+        for i in 0..3 {
+            m.m[i][3] = offset[i];
+        }
+        m
+    }
+
+    pub fn scaling(factors: Vec3) -> Self {
+        let mut m = Mat4::IDENTITY;
+        for i in 0..3 {
+            m.m[i][i] = factors[i];
+        }
+        m
+    }
+
+    // Counter-clockwise rotation by `degrees` when looking down `axis`
+    // towards the origin
+    pub fn rotation(axis: Vec3, degrees: f64) -> Self {
+        let a = Vec3::unit_vector(&axis);
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let t = 1.0 - cos;
+        let (x, y, z) = (a.x(), a.y(), a.z());
+        Mat4::new([
+            [
+                t * x * x + cos,
+                t * x * y - sin * z,
+                t * x * z + sin * y,
+                0.0,
+            ],
+            [
+                t * x * y + sin * z,
+                t * y * y + cos,
+                t * y * z - sin * x,
+                0.0,
+            ],
+            [
+                t * x * z - sin * y,
+                t * y * z + sin * x,
+                t * z * z + cos,
+                0.0,
+            ],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn rotation_x(degrees: f64) -> Self {
+        Mat4::rotation(Vec3::new(1.0, 0.0, 0.0), degrees)
+    }
+
+    pub fn rotation_y(degrees: f64) -> Self {
+        Mat4::rotation(Vec3::new(0.0, 1.0, 0.0), degrees)
+    }
+
+    pub fn rotation_z(degrees: f64) -> Self {
+        Mat4::rotation(Vec3::new(0.0, 0.0, 1.0), degrees)
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut t = Mat4::IDENTITY;
+        for (i, row) in self.m.iter().enumerate() {
+            for (j, &value) in row.iter().enumerate() {
+                t.m[j][i] = value;
+            }
+        }
+        t
+    }
+
+    // Gauss-Jordan elimination with partial pivoting, None when singular
+    pub fn inverse(&self) -> Option<Mat4> {
+        let mut a = self.m;
+        let mut inv = Mat4::IDENTITY.m;
+        for col in 0..4 {
+            let pivot = (col..4).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+            if a[pivot][col].abs() < 1e-12 {
+                return None;
+            }
+            a.swap(col, pivot);
+            inv.swap(col, pivot);
+
+            let scale = 1.0 / a[col][col];
+            for j in 0..4 {
+                a[col][j] *= scale;
+                inv[col][j] *= scale;
+            }
+            for row in 0..4 {
+                if row != col {
+                    let factor = a[row][col];
+                    for j in 0..4 {
+                        a[row][j] -= factor * a[col][j];
+                        inv[row][j] -= factor * inv[col][j];
+                    }
+                }
+            }
+        }
+        Some(Mat4 { m: inv })
+    }
+
+    pub fn transform_point(&self, p: &Point3) -> Point3 {
+        let m = &self.m;
+        let row = |i: usize| m[i][0] * p.x() + m[i][1] * p.y() + m[i][2] * p.z() + m[i][3];
+        Point3::new(row(0), row(1), row(2))
+    }
+
+    // Directions ignore the translation part
+    pub fn transform_vector(&self, v: &Vec3) -> Vec3 {
+        let m = &self.m;
+        let row = |i: usize| m[i][0] * v.x() + m[i][1] * v.y() + m[i][2] * v.z();
+        Vec3::new(row(0), row(1), row(2))
+    }
+}
+
+impl Default for Mat4 {
+    fn default() -> Self {
+        Mat4::IDENTITY
+    }
+}
+
+impl ops::Mul for Mat4 {
+    type Output = Mat4;
+    fn mul(self, rhs: Self) -> Self::Output {
+        let mut m = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..4).map(|k| self.m[i][k] * rhs.m[k][j]).sum();
+            }
+        }
+        Mat4 { m }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Vec3, b: Vec3) -> bool {
+        (a - b).length() < 1e-12
+    }
+
+    #[test]
+    fn composes_right_to_left() {
+        let m = Mat4::translation(Vec3::new(1.0, 2.0, 3.0)) * Mat4::rotation_y(90.0);
+        let p = m.transform_point(&Point3::new(1.0, 0.0, 0.0));
+        assert!(close(p, Point3::new(1.0, 2.0, 2.0)));
+        // Vectors are not translated
+        let v = m.transform_vector(&Vec3::new(1.0, 0.0, 0.0));
+        assert!(close(v, Vec3::new(0.0, 0.0, -1.0)));
+
+        let z = Mat4::rotation_z(90.0).transform_vector(&Vec3::new(1.0, 0.0, 0.0));
+        assert!(close(z, Vec3::new(0.0, 1.0, 0.0)));
+        let x = Mat4::rotation_x(90.0).transform_vector(&Vec3::new(0.0, 1.0, 0.0));
+        assert!(close(x, Vec3::new(0.0, 0.0, 1.0)));
+    }
+
+    #[test]
+    fn inverse_undoes_transform() {
+        let m = Mat4::translation(Vec3::new(-4.0, 0.5, 2.0))
+            * Mat4::rotation(Vec3::new(1.0, 1.0, 0.0), 33.0)
+            * Mat4::scaling(Vec3::new(2.0, 0.5, -3.0));
+        let inv = m.inverse().unwrap();
+        let p = Point3::new(0.3, -1.2, 5.0);
+        assert!(close(inv.transform_point(&m.transform_point(&p)), p));
+        let product = m * inv;
+        for i in 0..4 {
+            for j in 0..4 {
+                assert!((product.get(i, j) - Mat4::IDENTITY.get(i, j)).abs() < 1e-12);
+            }
+        }
+        assert_eq!(m.transpose().transpose(), m);
+
+        assert!(Mat4::scaling(Vec3::new(1.0, 0.0, 1.0)).inverse().is_none());
+    }
+}
@@ -5,6 +5,10 @@ use std::{
 
 use crate::utils::{random_f64, random_min_max};
 
+mod mat4;
+
+pub use mat4::Mat4;
+
 #[derive(Clone, Copy)]
 pub struct Vec3(f64, f64, f64);
 
@@ -60,7 +64,9 @@ impl Vec3 {
     pub fn random_in_unit_disk() -> Vec3 {
         loop {
             let p = Vec3::new(random_min_max(-1.0, 1.0), random_min_max(-1.0, 1.0), 0.0);
-            if p.length_squared() < 1.0 { return p };
+            if p.length_squared() < 1.0 {
+                return p;
+            };
         }
     }
 
@@ -121,6 +127,19 @@ impl Display for Vec3 {
     }
 }
 
+impl ops::Index<usize> for Vec3 {
+    type Output = f64;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        match index {
+            0 => &self.0,
+            1 => &self.1,
+            2 => &self.2,
+            _ => panic!("Vec3 index out of range: {}", index),
+        }
+    }
+}
+
 impl ops::Neg for Vec3 {
     type Output = Vec3;
 
@@ -147,7 +166,9 @@ impl ops::MulAssign for Vec3 {
 
 impl ops::DivAssign for Vec3 {
     fn div_assign(&mut self, rhs: Self) {
-        *self *= rhs;
+        self.0 /= rhs.0;
+        self.1 /= rhs.1;
+        self.2 /= rhs.2;
     }
 }
 